@@ -0,0 +1,111 @@
+// 衝突判定の絞り込み（SpatialGrid）と総当たりの比較
+// cargo bench --bench collision --no-default-features
+use std::hint::black_box;
+use std::time::Instant;
+use shooting_game::collision::Hitbox;
+use shooting_game::position::Position;
+use shooting_game::rng::Rng;
+use shooting_game::spatial_grid::SpatialGrid;
+
+const WIDTH: f32 = 800.0;
+const HEIGHT: f32 = 600.0;
+const ITERATIONS: u32 = 200;
+
+struct Entity {
+    position: Position,
+    hitbox: Hitbox,
+}
+
+fn scatter(rng: &mut Rng, count: usize, hitbox: Hitbox) -> Vec<Entity> {
+    (0..count)
+        .map(|_| Entity {
+            position: Position::new(rng.range(0.0, WIDTH), rng.range(0.0, HEIGHT)),
+            hitbox,
+        })
+        .collect()
+}
+
+// a の各要素と重なる b の要素の数を数える
+fn brute_force(a: &[Entity], b: &[Entity]) -> usize {
+    let mut hits = 0;
+    for x in a {
+        for y in b {
+            if x.hitbox.intersects(x.position, &y.hitbox, y.position) {
+                hits += 1;
+            }
+        }
+    }
+    hits
+}
+
+fn with_grid(grid: &mut SpatialGrid, candidates: &mut Vec<usize>, a: &[Entity], b: &[Entity]) -> usize {
+    grid.clear();
+    for (i, y) in b.iter().enumerate() {
+        grid.insert(i, y.position, &y.hitbox);
+    }
+
+    let mut hits = 0;
+    for x in a {
+        grid.query(x.position, &x.hitbox, candidates);
+        for &i in candidates.iter() {
+            let y = &b[i];
+            if x.hitbox.intersects(x.position, &y.hitbox, y.position) {
+                hits += 1;
+            }
+        }
+    }
+    hits
+}
+
+fn bench(name: &str, a: &[Entity], b: &[Entity], cell_size: f32) {
+    let mut grid = SpatialGrid::new(WIDTH, HEIGHT, cell_size);
+    let mut candidates = Vec::new();
+
+    let expected = brute_force(a, b);
+    let actual = with_grid(&mut grid, &mut candidates, a, b);
+    assert_eq!(expected, actual, "grid must find the same pairs as brute force");
+
+    let start = Instant::now();
+    for _ in 0..ITERATIONS {
+        black_box(brute_force(black_box(a), black_box(b)));
+    }
+    let brute = start.elapsed() / ITERATIONS;
+
+    let start = Instant::now();
+    for _ in 0..ITERATIONS {
+        black_box(with_grid(&mut grid, &mut candidates, black_box(a), black_box(b)));
+    }
+    let gridded = start.elapsed() / ITERATIONS;
+
+    println!(
+        "{:<40} brute force {:>10.3?}/tick   grid {:>10.3?}/tick   ({:.1}x, {} hits)",
+        name,
+        brute,
+        gridded,
+        brute.as_secs_f64() / gridded.as_secs_f64(),
+        actual,
+    );
+}
+
+fn main() {
+    let mut rng = Rng::new(1);
+
+    // 自機の弾 x 敵（Simulation::check_bullet_enemy_collisions）
+    for &(bullets, enemies) in &[(500, 10), (2000, 20), (5000, 40)] {
+        let a = scatter(&mut rng, bullets, Hitbox::circle(40.0));
+        let b = scatter(&mut rng, enemies, Hitbox::circle(88.0).with_offset(0.0, 12.0));
+        bench(&format!("{} player bullets x {} enemies", bullets, enemies), &a, &b, 128.0);
+    }
+
+    // 敵の弾 x プレイヤー（弾幕）
+    // 相手が1つだけなら総当たりのほうが速いので、Simulation はグリッドを使わない
+    for &bullets in &[2000, 5000, 10000] {
+        let a = scatter(&mut rng, 1, Hitbox::circle(12.0));
+        let b = scatter(&mut rng, bullets, Hitbox::circle(9.0));
+        bench(&format!("{} enemy bullets x player", bullets), &a, &b, 32.0);
+    }
+
+    // 敵の弾同士（多対多で弾が密集したときの目安）
+    let a = scatter(&mut rng, 2000, Hitbox::circle(9.0));
+    bench("2000 enemy bullets x 2000 enemy bullets", &a, &a, 32.0);
+}
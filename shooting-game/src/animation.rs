@@ -0,0 +1,132 @@
+use anyhow::{anyhow, Result};
+use serde::{Deserialize, Serialize};
+
+// 最後のコマまで進んだあとの動き
+#[derive(Clone, Copy, PartialEq, Debug, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LoopMode {
+    // 最初のコマに戻って繰り返す
+    #[default]
+    Loop,
+    // 最後のコマで止まる
+    Once,
+    // 逆順に戻って往復する
+    PingPong,
+}
+
+// スプライトシートのコマを順に切り替えるアニメーション
+// JSON では { "frames": [0, 1, 2], "frame_time": 0.1, "mode": "loop" } のように書く
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Animation {
+    pub frames: Vec<u32>, // 表示するコマの番号
+    #[serde(default = "default_frame_time")]
+    pub frame_time: f32, // 1コマの表示時間（秒単位）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub durations: Option<Vec<f32>>, // コマごとの表示時間（あれば frame_time より優先）
+    #[serde(default)]
+    pub mode: LoopMode,
+}
+
+fn default_frame_time() -> f32 {
+    0.1
+}
+
+impl Animation {
+    // i 番目のコマの表示時間
+    pub fn duration(&self, i: usize) -> f32 {
+        self.durations
+            .as_ref()
+            .and_then(|durations| durations.get(i).copied())
+            .unwrap_or(self.frame_time)
+    }
+
+    // 1周（PingPong なら片道）にかかる時間
+    pub fn total_duration(&self) -> f32 {
+        (0..self.frames.len()).map(|i| self.duration(i)).sum()
+    }
+
+    // 再生を始めてから time 秒後に表示するコマの番号
+    pub fn frame_at(&self, time: f32) -> u32 {
+        let count = self.frames.len();
+        let total = self.total_duration();
+        if count <= 1 || total <= 0.0 {
+            return self.frames.first().copied().unwrap_or(0);
+        }
+
+        let index = match self.mode {
+            LoopMode::Once if time >= total => count - 1,
+            LoopMode::Once => self.index_at(time),
+            LoopMode::Loop => self.index_at(time % total),
+            LoopMode::PingPong => {
+                // 偶数周目は順に、奇数周目は逆順に進む（折り返しのコマは2回続けて表示される）
+                let index = self.index_at(time % total);
+                if ((time / total) as u32).is_multiple_of(2) { index } else { count - 1 - index }
+            }
+        };
+        self.frames[index]
+    }
+
+    fn index_at(&self, time: f32) -> usize {
+        let mut elapsed = 0.0;
+        for i in 0..self.frames.len() {
+            elapsed += self.duration(i);
+            if time < elapsed {
+                return i;
+            }
+        }
+        self.frames.len() - 1
+    }
+
+    // frame_count コマのシートで再生できるか確かめる
+    pub fn validate(&self, frame_count: u32) -> Result<()> {
+        if self.frames.is_empty() {
+            return Err(anyhow!("animation has no frames"));
+        }
+        if let Some(frame) = self.frames.iter().find(|frame| **frame >= frame_count) {
+            return Err(anyhow!("frame {} is out of range (the sheet has {} frames)", frame, frame_count));
+        }
+        if let Some(durations) = &self.durations {
+            if durations.len() != self.frames.len() {
+                return Err(anyhow!("{} durations for {} frames", durations.len(), self.frames.len()));
+            }
+        }
+        if (0..self.frames.len()).any(|i| self.duration(i) <= 0.0) {
+            return Err(anyhow!("frame durations must be positive"));
+        }
+        Ok(())
+    }
+}
+
+// エンティティごとのアニメーションの再生状況
+// どのアニメーションをいつから再生しているかだけを持ち、コマの決め方は描画側の Animation に任せる
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct AnimationState {
+    clip: &'static str, // 再生中のアニメーションの名前
+    time: f32,          // 再生を始めてからの時間（秒単位）
+}
+
+impl AnimationState {
+    pub fn new(clip: &'static str) -> AnimationState {
+        AnimationState { clip, time: 0.0 }
+    }
+
+    // 別のアニメーションに切り替える（同じものなら続きから再生する）
+    pub fn play(&mut self, clip: &'static str) {
+        if self.clip != clip {
+            self.clip = clip;
+            self.time = 0.0;
+        }
+    }
+
+    pub fn update(&mut self, dt: f32) {
+        self.time += dt;
+    }
+
+    pub fn clip(&self) -> &'static str {
+        self.clip
+    }
+
+    pub fn time(&self) -> f32 {
+        self.time
+    }
+}
@@ -0,0 +1,237 @@
+use std::collections::{BTreeMap, HashSet};
+use std::sync::OnceLock;
+use anyhow::{anyhow, Context, Result};
+use serde::Deserialize;
+use crate::collision::Hitbox;
+use crate::animation::Animation;
+
+// 組み込みの素材の一覧（パスは index.html からの相対パス）
+const DEFAULT_MANIFEST: &str = include_str!("../assets.json");
+
+// 読み込む素材の一覧
+// 今は画像だけだが、音やフォントも同じように種類ごとの一覧を足していく
+#[derive(Clone, Debug, Deserialize)]
+pub struct AssetManifest {
+    pub images: Vec<ImageAsset>,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct ImageAsset {
+    pub name: String, // 描画するときに使う名前（スプライト名）
+    pub path: String,
+    pub width: u32, // 画像ファイルの大きさ（検証ツールで実際の大きさと比べる）
+    pub height: u32,
+    #[serde(default)]
+    pub frame: Option<FrameSize>, // 1コマの大きさ（なければ画像全体で1コマ）
+    #[serde(default = "default_anchor")]
+    pub anchor: [f32; 2], // 描画位置に合わせる点（コマの大きさに対する割合、[0.5, 0.5] が中心）
+    #[serde(default)]
+    pub hitbox: Option<Hitbox>, // 当たり判定の既定値（画像の元の大きさでの値）
+    #[serde(default)]
+    pub animations: BTreeMap<String, Animation>, // このシートのコマを使うアニメーション
+    #[serde(default = "default_required")]
+    pub required: bool, // true なら読み込めるまでゲームを始めない
+}
+
+// 読み込みの進み具合
+#[derive(Clone, Copy, Debug)]
+pub struct LoadProgress {
+    pub loaded: usize,
+    pub failed: usize,
+    pub total: usize,
+}
+
+impl LoadProgress {
+    // 0.0〜1.0（失敗したものも終わったものとして数える）
+    pub fn fraction(&self) -> f64 {
+        if self.total == 0 {
+            1.0
+        } else {
+            (self.loaded + self.failed) as f64 / self.total as f64
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Deserialize)]
+pub struct FrameSize {
+    pub width: u32,
+    pub height: u32,
+}
+
+fn default_anchor() -> [f32; 2] {
+    [0.5, 0.5]
+}
+
+fn default_required() -> bool {
+    true
+}
+
+impl ImageAsset {
+    pub fn frame_size(&self) -> FrameSize {
+        self.frame.unwrap_or(FrameSize { width: self.width, height: self.height })
+    }
+
+    // シートに入っているコマの数（左上から横に並べ、右端まで来たら次の行に進む）
+    pub fn frame_count(&self) -> u32 {
+        let frame = self.frame_size();
+        (self.width / frame.width) * (self.height / frame.height)
+    }
+
+    // ファイルを開かなくても分かる矛盾を調べる
+    fn validate(&self) -> Result<()> {
+        // 0 はどのコマの大きさでも割り切れるので、ここで弾かないとコマが1つもないシートになる
+        if self.width == 0 || self.height == 0 {
+            return Err(anyhow!("image size must be positive"));
+        }
+        let frame = self.frame_size();
+        if frame.width == 0 || frame.height == 0 {
+            return Err(anyhow!("frame size must be positive"));
+        }
+        if !self.width.is_multiple_of(frame.width) || !self.height.is_multiple_of(frame.height) {
+            return Err(anyhow!(
+                "{}x{} image cannot be divided into {}x{} frames",
+                self.width, self.height, frame.width, frame.height
+            ));
+        }
+        if self.anchor.iter().any(|a| !(0.0..=1.0).contains(a)) {
+            return Err(anyhow!("anchor {:?} must be within 0.0..=1.0", self.anchor));
+        }
+        if let Some(hitbox) = &self.hitbox {
+            let (half_width, half_height) = hitbox.half_extents();
+            if hitbox.offset_x.abs() + half_width > frame.width as f32 / 2.0
+                || hitbox.offset_y.abs() + half_height > frame.height as f32 / 2.0
+            {
+                return Err(anyhow!("hitbox extends outside the {}x{} frame", frame.width, frame.height));
+            }
+        }
+        for (name, animation) in &self.animations {
+            animation
+                .validate(self.frame_count())
+                .with_context(|| format!("animation \"{}\"", name))?;
+        }
+        Ok(())
+    }
+}
+
+impl AssetManifest {
+    pub fn from_json(json: &str) -> Result<AssetManifest> {
+        let manifest: AssetManifest = serde_json::from_str(json).context("failed to parse asset manifest")?;
+        manifest.validate()?;
+        Ok(manifest)
+    }
+
+    pub fn from_file(path: &str) -> Result<AssetManifest> {
+        let json = std::fs::read_to_string(path).with_context(|| format!("failed to read {}", path))?;
+        AssetManifest::from_json(&json)
+    }
+
+    fn validate(&self) -> Result<()> {
+        let mut names = HashSet::new();
+        for image in &self.images {
+            if !names.insert(image.name.as_str()) {
+                return Err(anyhow!("image \"{}\": declared more than once", image.name));
+            }
+            image.validate().with_context(|| format!("image \"{}\"", image.name))?;
+        }
+        Ok(())
+    }
+
+    pub fn image(&self, name: &str) -> Option<&ImageAsset> {
+        self.images.iter().find(|image| image.name == name)
+    }
+
+    // name の画像に書いた当たり判定を、1コマを width x height の大きさで描いたときの値にする
+    pub fn hitbox(&self, name: &str, width: f32, height: f32) -> Option<Hitbox> {
+        let image = self.image(name)?;
+        let frame = image.frame_size();
+        let hitbox = image.hitbox?;
+        Some(hitbox.scaled(width / frame.width as f32, height / frame.height as f32))
+    }
+
+    // name の画像のアニメーション clip の1周の長さ（秒単位）
+    pub fn clip_duration(&self, name: &str, clip: &str) -> Option<f32> {
+        self.image(name)?.animations.get(clip).map(|animation| animation.total_duration())
+    }
+
+    // 組み込みの素材の一覧（一度だけ読む）
+    pub fn built_in() -> &'static AssetManifest {
+        static MANIFEST: OnceLock<AssetManifest> = OnceLock::new();
+        MANIFEST.get_or_init(AssetManifest::default)
+    }
+}
+
+// 組み込みの素材の一覧に書いた当たり判定を width x height の大きさに合わせたもの
+// プレイヤー、敵、弾の当たり判定はここから作るので、assets.json の値だけを直せばよい
+// （読み込む画像を atlas などで差し替えても、当たり判定はゲームの動きが変わらないよう組み込みのものを使う）
+pub fn default_hitbox(name: &str, width: f32, height: f32) -> Hitbox {
+    AssetManifest::built_in()
+        .hitbox(name, width, height)
+        .unwrap_or_else(|| panic!("built-in asset manifest should declare a hitbox for \"{}\"", name))
+}
+
+impl Default for AssetManifest {
+    fn default() -> AssetManifest {
+        AssetManifest::from_json(DEFAULT_MANIFEST).expect("built-in asset manifest should be valid")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::collision::Shape;
+
+    #[test]
+    fn hitbox_is_scaled_from_frame_to_entity_size() {
+        let manifest = AssetManifest::from_json(r#"{ "images": [
+            { "name": "ship", "path": "ship.png", "width": 400, "height": 100,
+              "frame": { "width": 100, "height": 100 },
+              "hitbox": { "type": "rect", "width": 40, "height": 60, "offset_x": 10, "offset_y": -20 } },
+            { "name": "plain", "path": "plain.png", "width": 10, "height": 10 }
+        ] }"#).unwrap();
+
+        let hitbox = manifest.hitbox("ship", 50.0, 200.0).unwrap();
+        assert_eq!(hitbox.shape, Shape::Rect { width: 20.0, height: 120.0 });
+        assert_eq!((hitbox.offset_x, hitbox.offset_y), (5.0, -40.0));
+        assert!(manifest.hitbox("plain", 10.0, 10.0).is_none());
+        assert!(manifest.hitbox("missing", 10.0, 10.0).is_none());
+    }
+
+    #[test]
+    fn built_in_manifest_declares_every_entity_hitbox() {
+        for (name, width, height) in [
+            ("player", 180.0, 180.0),
+            ("bullet", 90.0, 90.0),
+            ("laser", 24.0, 90.0),
+            ("enemy_bullet", 24.0, 24.0),
+            ("enemy", 216.0, 216.0),
+            ("enemy_fast", 144.0, 144.0),
+            ("enemy_strong", 288.0, 288.0),
+        ] {
+            assert!(AssetManifest::built_in().hitbox(name, width, height).is_some(), "{}", name);
+        }
+        // 画像の大きさで書いた半径が、描く大きさに合わせて縮む
+        let Shape::Circle { radius } = default_hitbox("enemy_fast", 144.0, 144.0).shape else {
+            panic!("enemy_fast hitbox should be a circle");
+        };
+        assert!((radius - 58.0).abs() < 0.01, "{}", radius);
+    }
+
+    #[test]
+    fn hitbox_outside_the_frame_is_rejected() {
+        let result = AssetManifest::from_json(r#"{ "images": [
+            { "name": "ship", "path": "ship.png", "width": 100, "height": 100,
+              "hitbox": { "type": "circle", "radius": 45, "offset_x": 10 } }
+        ] }"#);
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn zero_sized_image_is_rejected() {
+        for (width, height) in [(0, 100), (100, 0)] {
+            let json = format!(r#"{{ "images": [
+                {{ "name": "empty", "path": "empty.png", "width": {}, "height": {}, "frame": {{ "width": 50, "height": 50 }} }}
+            ] }}"#, width, height);
+            assert!(AssetManifest::from_json(&json).is_err(), "{}x{}", width, height);
+        }
+    }
+}
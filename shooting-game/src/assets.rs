@@ -0,0 +1,124 @@
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::rc::Rc;
+use wasm_bindgen::{prelude::Closure, JsCast, JsValue};
+use wasm_bindgen_futures::{spawn_local, JsFuture};
+use web_sys::HtmlImageElement;
+use crate::asset_manifest::{AssetManifest, ImageAsset, LoadProgress};
+
+// 素材1つの読み込み状況
+#[derive(Clone, PartialEq, Debug)]
+pub enum AssetStatus {
+    Pending,
+    Loaded,
+    Failed(String), // 読み込めなかった理由
+}
+
+struct AssetStore {
+    manifest: AssetManifest,
+    images: HashMap<String, HtmlImageElement>,
+    status: HashMap<String, AssetStatus>,
+}
+
+// 読み込んだ素材を共有するハンドル
+// clone しても同じ中身を指すので、読み込みが終わった画像は Renderer からもすぐに使える
+#[derive(Clone)]
+pub struct Assets {
+    store: Rc<RefCell<AssetStore>>,
+}
+
+impl Assets {
+    // manifest にある素材をすべて読み込み始める
+    pub fn load(manifest: AssetManifest) -> Assets {
+        let status = manifest
+            .images
+            .iter()
+            .map(|image| (image.name.clone(), AssetStatus::Pending))
+            .collect();
+        let images = manifest.images.clone();
+        let assets = Assets {
+            store: Rc::new(RefCell::new(AssetStore { manifest, images: HashMap::new(), status })),
+        };
+
+        for image in images {
+            let store = assets.store.clone();
+            spawn_local(async move {
+                let result = load_image(&image).await;
+                let mut store = store.borrow_mut();
+                match result {
+                    Ok(element) => {
+                        store.images.insert(image.name.clone(), element);
+                        store.status.insert(image.name, AssetStatus::Loaded);
+                    }
+                    Err(message) => {
+                        web_sys::console::error_1(&message.clone().into());
+                        store.status.insert(image.name, AssetStatus::Failed(message));
+                    }
+                }
+            });
+        }
+
+        assets
+    }
+
+    // 読み込みが終わっていればその画像
+    pub fn image(&self, name: &str) -> Option<HtmlImageElement> {
+        self.store.borrow().images.get(name).cloned()
+    }
+
+    pub fn progress(&self) -> LoadProgress {
+        let store = self.store.borrow();
+        let count = |f: fn(&AssetStatus) -> bool| store.status.values().filter(|status| f(status)).count();
+        LoadProgress {
+            loaded: count(|status| *status == AssetStatus::Loaded),
+            failed: count(|status| matches!(status, AssetStatus::Failed(_))),
+            total: store.status.len(),
+        }
+    }
+
+    // 必須の素材がすべて読み込めたかどうか
+    pub fn is_ready(&self) -> bool {
+        let store = self.store.borrow();
+        store
+            .manifest
+            .images
+            .iter()
+            .filter(|image| image.required)
+            .all(|image| store.status.get(&image.name) == Some(&AssetStatus::Loaded))
+    }
+
+    // 読み込めなかった必須の素材のエラー（これがあるとゲームを始められない）
+    pub fn errors(&self) -> Vec<String> {
+        let store = self.store.borrow();
+        store
+            .manifest
+            .images
+            .iter()
+            .filter(|image| image.required)
+            .filter_map(|image| match store.status.get(&image.name) {
+                Some(AssetStatus::Failed(message)) => Some(message.clone()),
+                _ => None,
+            })
+            .collect()
+    }
+}
+
+// onload と onerror を登録してから src を設定し、どちらかが呼ばれるまで待つ
+async fn load_image(asset: &ImageAsset) -> Result<HtmlImageElement, String> {
+    let image = HtmlImageElement::new().map_err(|err| format!("{:?}", err))?;
+
+    let promise = js_sys::Promise::new(&mut |resolve, reject| {
+        image.set_onload(Some(Closure::once_into_js(move || {
+            resolve.call0(&JsValue::NULL).unwrap();
+        }).unchecked_ref()));
+        image.set_onerror(Some(Closure::once_into_js(move || {
+            reject.call0(&JsValue::NULL).unwrap();
+        }).unchecked_ref()));
+    });
+    image.set_src(&asset.path);
+
+    JsFuture::from(promise)
+        .await
+        .map_err(|_| format!("failed to load image \"{}\" from {} (missing file or not an image)", asset.name, asset.path))?;
+    Ok(image)
+}
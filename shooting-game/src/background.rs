@@ -0,0 +1,196 @@
+use anyhow::{anyhow, Result};
+use serde::Deserialize;
+use crate::rng::Rng;
+
+// ステージの背景の設定（ステージスクリプトの "background"）
+// 先に書いた層ほど奥に描く。画像の層は画面全体を覆うので、透明な部分のない画像なら一番奥に置く
+// （その奥に置いた層は見えなくなる）
+//
+//   "background": { "layers": [
+//     { "type": "image", "speed": 15, "sprite": "background" },   // "flip": true で1枚おきに上下を反転
+//     { "type": "starfield", "speed": 40, "count": 80, "size": [1, 2], "brightness": [0.3, 0.6] },
+//     { "type": "starfield", "speed": 160, "count": 20, "size": [2, 3] }
+//   ] }
+#[derive(Clone, Debug, Deserialize)]
+pub struct BackgroundConfig {
+    pub layers: Vec<BackgroundLayer>,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct BackgroundLayer {
+    pub speed: f32, // 下へ流れる速さ（1秒あたりのピクセル数）
+    #[serde(flatten)]
+    pub kind: LayerKind,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum LayerKind {
+    // 画像を画面の幅に合わせて縦に敷き詰める
+    // 上下の端がつながる画像を使う。つながらない画像は flip で1枚おきに上下を反転させられるが、
+    // 景色の絵だと逆さまになるので、模様のような絵のときだけ使う
+    Image {
+        sprite: String,
+        #[serde(default)]
+        flip: bool,
+    },
+    // 乱数で作る星空（画像がなくても描ける）
+    Starfield {
+        count: u32,
+        #[serde(default = "default_star_size")]
+        size: [f32; 2], // 星の大きさの最小と最大（ピクセル単位）
+        #[serde(default = "default_star_brightness")]
+        brightness: [f32; 2], // 星の明るさの最小と最大（0.0〜1.0）
+        #[serde(default)]
+        seed: u64,
+    },
+}
+
+fn default_star_size() -> [f32; 2] {
+    [1.0, 2.0]
+}
+
+fn default_star_brightness() -> [f32; 2] {
+    [0.4, 1.0]
+}
+
+impl BackgroundConfig {
+    pub fn validate(&self) -> Result<()> {
+        for (i, layer) in self.layers.iter().enumerate() {
+            if let LayerKind::Starfield { size, brightness, .. } = &layer.kind {
+                if size[0] <= 0.0 || size[0] > size[1] {
+                    return Err(anyhow!("background layer {}: size must be positive and in ascending order", i));
+                }
+                if brightness[0] > brightness[1] || brightness.iter().any(|b| !(0.0..=1.0).contains(b)) {
+                    return Err(anyhow!("background layer {}: brightness must be within 0.0..=1.0 and in ascending order", i));
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+// 組み込みの背景（背景画像、奥の星空、手前の星空）
+impl Default for BackgroundConfig {
+    fn default() -> BackgroundConfig {
+        BackgroundConfig {
+            layers: vec![
+                BackgroundLayer { speed: 15.0, kind: LayerKind::Image { sprite: "background".to_string(), flip: false } },
+                BackgroundLayer {
+                    speed: 40.0,
+                    kind: LayerKind::Starfield { count: 80, size: [1.0, 2.0], brightness: [0.3, 0.6], seed: 1 },
+                },
+                BackgroundLayer {
+                    speed: 160.0,
+                    kind: LayerKind::Starfield { count: 20, size: [2.0, 3.0], brightness: [0.6, 1.0], seed: 2 },
+                },
+            ],
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct Star {
+    pub x: f32,
+    pub y: f32, // 流れる前の位置
+    pub size: f32,
+    pub brightness: f32,
+}
+
+// 背景の層1つ分の状態
+#[derive(Clone, Debug)]
+pub struct LayerState {
+    pub layer: BackgroundLayer,
+    pub stars: Vec<Star>, // 星空のときだけ使う
+    distance: f64,          // 流れた距離（ピクセル単位）
+    previous_distance: f64, // 1ステップ前の distance（描画の補間用）
+}
+
+impl LayerState {
+    // 描画するときの流れた距離
+    // 敷き詰めるときの繰り返しの長さで割った余りは描画側で取る
+    pub fn interpolated_distance(&self, alpha: f32) -> f64 {
+        self.previous_distance + (self.distance - self.previous_distance) * alpha as f64
+    }
+}
+
+// ステージの背景
+// ゲームの展開とは関係ないので、星の配置は層ごとの seed から作る
+#[derive(Clone, Debug)]
+pub struct Background {
+    layers: Vec<LayerState>,
+}
+
+impl Background {
+    pub fn new(config: &BackgroundConfig, width: f32, height: f32) -> Background {
+        let layers = config
+            .layers
+            .iter()
+            .map(|layer| LayerState {
+                stars: generate_stars(&layer.kind, width, height),
+                layer: layer.clone(),
+                distance: 0.0,
+                previous_distance: 0.0,
+            })
+            .collect();
+        Background { layers }
+    }
+
+    pub fn update(&mut self, dt: f32) {
+        for state in &mut self.layers {
+            state.previous_distance = state.distance;
+            state.distance += (state.layer.speed * dt) as f64;
+        }
+    }
+
+    // 流れた距離を最初に戻す（星の配置はそのまま）
+    pub fn reset(&mut self) {
+        for state in &mut self.layers {
+            state.distance = 0.0;
+            state.previous_distance = 0.0;
+        }
+    }
+
+    pub fn layers(&self) -> &[LayerState] {
+        &self.layers
+    }
+}
+
+fn generate_stars(kind: &LayerKind, width: f32, height: f32) -> Vec<Star> {
+    let LayerKind::Starfield { count, size, brightness, seed } = kind else {
+        return Vec::new();
+    };
+    let mut rng = Rng::new(*seed);
+    (0..*count)
+        .map(|_| Star {
+            x: rng.range(0.0, width),
+            y: rng.range(0.0, height),
+            size: rng.range(size[0], size[1]),
+            brightness: rng.range(brightness[0], brightness[1]),
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn image_layers_do_not_flip_unless_asked() {
+        let config: BackgroundConfig = serde_json::from_str(r#"{ "layers": [
+            { "type": "image", "speed": 15, "sprite": "background" },
+            { "type": "image", "speed": 40, "sprite": "pattern", "flip": true }
+        ] }"#).unwrap();
+        let flips: Vec<bool> = config
+            .layers
+            .iter()
+            .map(|layer| matches!(layer.kind, LayerKind::Image { flip: true, .. }))
+            .collect();
+        assert_eq!(flips, [false, true]);
+
+        assert!(BackgroundConfig::default()
+            .layers
+            .iter()
+            .all(|layer| !matches!(layer.kind, LayerKind::Image { flip: true, .. })));
+    }
+}
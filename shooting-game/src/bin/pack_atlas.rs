@@ -0,0 +1,170 @@
+// 素材の一覧（assets.json）にある画像のコマを1枚の画像にまとめるツール
+//
+//   cargo run --bin pack_atlas -- [assets.json] [出力先のディレクトリ]
+//
+// 出力先（既定は images）に atlas.png と、各コマの位置を書いた atlas.json を書き出す
+// atlas.json は GameHandle の atlas オプションにそのまま渡せる
+use std::collections::BTreeMap;
+use std::path::Path;
+use anyhow::{anyhow, Context, Result};
+use image::{GenericImageView, RgbaImage};
+use shooting_game::asset_manifest::AssetManifest;
+use shooting_game::sprite_atlas::{AtlasSprite, PackedAtlas, Rect};
+
+const MAX_WIDTH: u32 = 2048; // まとめた画像の最大の幅
+const PADDING: u32 = 2; // 隣のコマの色がにじまないように空ける間隔
+
+// 詰め込む前のコマ1つ
+struct Frame {
+    sprite: String,
+    index: usize,
+    image: RgbaImage,
+}
+
+fn main() -> Result<()> {
+    let manifest_path = std::env::args().nth(1).unwrap_or_else(|| "assets.json".to_string());
+    let output_dir = std::env::args().nth(2).unwrap_or_else(|| "images".to_string());
+
+    let manifest = AssetManifest::from_file(&manifest_path)?;
+    let root = Path::new(&manifest_path).parent().unwrap_or(Path::new("."));
+
+    // シートをコマごとに切り分ける
+    let mut frames = Vec::new();
+    for asset in &manifest.images {
+        let path = root.join(&asset.path);
+        let sheet = image::open(&path)
+            .with_context(|| format!("failed to open {}", path.display()))?
+            .to_rgba8();
+        let frame = asset.frame_size();
+        let columns = sheet.width() / frame.width;
+        for index in 0..asset.frame_count() {
+            let x = (index % columns) * frame.width;
+            let y = (index / columns) * frame.height;
+            let image = sheet.view(x, y, frame.width, frame.height).to_image();
+            frames.push(Frame { sprite: asset.name.clone(), index: index as usize, image });
+        }
+    }
+
+    let (rects, width, height) = pack(&frames)?;
+
+    let mut atlas_image = RgbaImage::new(width, height);
+    for (frame, rect) in frames.iter().zip(&rects) {
+        image::imageops::replace(&mut atlas_image, &frame.image, rect.x as i64, rect.y as i64);
+    }
+
+    let mut sprites = BTreeMap::new();
+    for asset in &manifest.images {
+        let mut sprite_frames = vec![Rect { x: 0, y: 0, width: 0, height: 0 }; asset.frame_count() as usize];
+        for (frame, rect) in frames.iter().zip(&rects).filter(|(frame, _)| frame.sprite == asset.name) {
+            sprite_frames[frame.index] = *rect;
+        }
+        sprites.insert(
+            asset.name.clone(),
+            AtlasSprite {
+                image: PackedAtlas::IMAGE_NAME.to_string(),
+                frames: sprite_frames,
+                anchor: asset.anchor,
+                animations: asset.animations.clone(),
+            },
+        );
+    }
+
+    // atlas.json の画像のパスは assets.json と同じく index.html からの相対パス
+    let output_dir = Path::new(&output_dir);
+    let image_path = output_dir.join("atlas.png");
+    let relative_image_path = image_path.strip_prefix(root).unwrap_or(&image_path);
+    let atlas = PackedAtlas {
+        image: relative_image_path.to_string_lossy().into_owned(),
+        width,
+        height,
+        sprites,
+    };
+
+    atlas_image
+        .save(&image_path)
+        .with_context(|| format!("failed to write {}", image_path.display()))?;
+    let json_path = output_dir.join("atlas.json");
+    std::fs::write(&json_path, serde_json::to_string_pretty(&atlas)? + "\n")
+        .with_context(|| format!("failed to write {}", json_path.display()))?;
+
+    println!(
+        "packed {} frames from {} images into {} ({}x{})",
+        frames.len(),
+        manifest.images.len(),
+        image_path.display(),
+        width,
+        height
+    );
+    Ok(())
+}
+
+// 背の高い順に、左から右へ棚に並べるように詰める
+// 返り値はコマごとの位置（frames と同じ順）と、まとめた画像の幅と高さ
+fn pack(frames: &[Frame]) -> Result<(Vec<Rect>, u32, u32)> {
+    let mut order: Vec<usize> = (0..frames.len()).collect();
+    order.sort_by_key(|&i| std::cmp::Reverse((frames[i].image.height(), frames[i].image.width())));
+
+    let mut rects = vec![Rect { x: 0, y: 0, width: 0, height: 0 }; frames.len()];
+    let (mut x, mut y, mut shelf_height, mut width) = (0, 0, 0, 0);
+    for i in order {
+        let (w, h) = frames[i].image.dimensions();
+        if w > MAX_WIDTH {
+            return Err(anyhow!("frame {} of \"{}\" is wider than {}px", frames[i].index, frames[i].sprite, MAX_WIDTH));
+        }
+        if x + w > MAX_WIDTH {
+            // 棚がいっぱいになったら次の棚へ
+            x = 0;
+            y += shelf_height + PADDING;
+            shelf_height = 0;
+        }
+        rects[i] = Rect { x, y, width: w, height: h };
+        x += w + PADDING;
+        shelf_height = shelf_height.max(h);
+        width = width.max(x - PADDING);
+    }
+    Ok((rects, width, y + shelf_height))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn frames(sizes: &[(u32, u32)]) -> Vec<Frame> {
+        sizes
+            .iter()
+            .enumerate()
+            .map(|(index, &(width, height))| Frame { sprite: "test".to_string(), index, image: RgbaImage::new(width, height) })
+            .collect()
+    }
+
+    fn overlaps(a: &Rect, b: &Rect) -> bool {
+        a.x < b.x + b.width && b.x < a.x + a.width && a.y < b.y + b.height && b.y < a.y + a.height
+    }
+
+    #[test]
+    fn packs_frames_without_overlap_inside_the_atlas() {
+        let frames = frames(&[(100, 50), (300, 200), (1500, 80), (700, 200), (20, 20), (900, 60)]);
+        let (rects, width, height) = pack(&frames).unwrap();
+
+        assert!(width <= MAX_WIDTH);
+        for (frame, rect) in frames.iter().zip(&rects) {
+            assert_eq!((rect.width, rect.height), frame.image.dimensions());
+            assert!(rect.x + rect.width <= width && rect.y + rect.height <= height, "{:?}", rect);
+        }
+        for (i, a) in rects.iter().enumerate() {
+            for b in &rects[i + 1..] {
+                assert!(!overlaps(a, b), "{:?} overlaps {:?}", a, b);
+            }
+        }
+        // 1つの棚に収まらないので2段以上になる
+        assert!(rects.iter().any(|rect| rect.y > 0));
+    }
+
+    #[test]
+    fn rejects_frames_wider_than_the_atlas() {
+        assert!(pack(&frames(&[(MAX_WIDTH + 1, 10)])).is_err());
+        let (rects, width, height) = pack(&frames(&[(MAX_WIDTH, 10)])).unwrap();
+        assert_eq!(rects[0], Rect { x: 0, y: 0, width: MAX_WIDTH, height: 10 });
+        assert_eq!((width, height), (MAX_WIDTH, 10));
+    }
+}
@@ -0,0 +1,64 @@
+// ブラウザなしでゲームを動かし、画面を PNG に書き出すツール
+//
+//   cargo run --bin render_frames -- [シード] [ステップ数] [出力先のディレクトリ] [書き出す間隔]
+//
+// 左右に揺れながら撃ち続ける自動操縦で遊び、書き出す間隔（ステップ数）ごとに
+// frame_00060.png のような名前で保存する。画像は assets.json から読む
+use std::path::Path;
+use anyhow::{Context, Result};
+use shooting_game::asset_manifest::AssetManifest;
+use shooting_game::input::Input;
+use shooting_game::renderer::Renderer;
+use shooting_game::simulation::Simulation;
+use shooting_game::software_backend::SoftwareBackend;
+use shooting_game::sprite_atlas::SpriteAtlas;
+use shooting_game::statistics::Statistics;
+use shooting_game::event::GameEvent;
+
+const WIDTH: u32 = 800;
+const HEIGHT: u32 = 600;
+const DT: f64 = 1.0 / 60.0;
+
+fn main() -> Result<()> {
+    let arg = |i: usize| std::env::args().nth(i);
+    let seed: u64 = arg(1).map_or(Ok(1), |s| s.parse()).context("seed must be a number")?;
+    let ticks: u64 = arg(2).map_or(Ok(600), |s| s.parse()).context("tick count must be a number")?;
+    let output_dir = arg(3).unwrap_or_else(|| "frames".to_string());
+    let interval: u64 = arg(4).map_or(Ok(60), |s| s.parse()).context("interval must be a number")?;
+
+    let manifest = AssetManifest::default();
+    let mut backend = SoftwareBackend::new(WIDTH, HEIGHT);
+    backend.load_images(&manifest, Path::new("."))?;
+    let mut renderer = Renderer::new(backend, SpriteAtlas::from_manifest(&manifest));
+
+    let output_dir = Path::new(&output_dir);
+    std::fs::create_dir_all(output_dir).with_context(|| format!("failed to create {}", output_dir.display()))?;
+
+    let mut simulation = Simulation::new(WIDTH as f32, HEIGHT as f32, seed);
+    let mut statistics = Statistics::default();
+    simulation.start();
+
+    for tick in 1..=ticks {
+        simulation.step(&autopilot(tick), DT);
+        for event in simulation.drain_events() {
+            if let GameEvent::GameStarted { .. } = event {
+                statistics = Statistics::default();
+            }
+            statistics.record(&event);
+        }
+
+        if tick.is_multiple_of(interval.max(1)) || tick == ticks {
+            renderer.render(&simulation, &statistics, 1.0);
+            let path = output_dir.join(format!("frame_{:05}.png", tick));
+            renderer.backend().save(&path)?;
+            println!("{} ({}, score {})", path.display(), simulation.state().name(), simulation.score());
+        }
+    }
+    Ok(())
+}
+
+// 2秒ごとに左右を入れ替えながら撃ち続ける
+fn autopilot(tick: u64) -> Input {
+    let left = (tick / 120).is_multiple_of(2);
+    Input { left, right: !left, fire: true, ..Input::default() }
+}
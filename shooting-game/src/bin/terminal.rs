@@ -0,0 +1,565 @@
+// 端末で遊ぶためのフロントエンド（SSH の先でも遊べる）
+//
+//   cargo run --release --features terminal --bin terminal -- [--stage ステージのファイル] [--escape-penalty 点数] [シード]
+//
+// 画面は上半分のブロック文字（▀）の文字色と背景色で1文字に縦2ピクセルを描く（24bit カラーの端末向け）
+// 矢印キーで移動、スペースで発射、1〜4 で武器、Enter で開始、Esc で一時停止、C でコンティニュー、Q で終了
+//
+// ほとんどの端末はキーを離したことを知らせないので、キーリピートが続いている間は押しているとみなす
+// 離したことを知らせてくれる端末（kitty のキーボードプロトコル）ではそれを使う
+use std::io::{self, Write};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+use anyhow::{bail, Context, Result};
+use crossterm::cursor::{Hide, MoveTo, Show};
+use crossterm::event::{
+    self, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers, KeyboardEnhancementFlags,
+    PopKeyboardEnhancementFlags, PushKeyboardEnhancementFlags,
+};
+use crossterm::style::{Color, Print, ResetColor, SetBackgroundColor, SetForegroundColor};
+use crossterm::terminal::{self, Clear, ClearType, EnterAlternateScreen, LeaveAlternateScreen};
+use crossterm::{execute, queue};
+use shooting_game::background::LayerKind;
+use shooting_game::collision::Hitbox;
+use shooting_game::config::GameConfig;
+use shooting_game::enemy_type::EnemyType;
+use shooting_game::event::GameEvent;
+use shooting_game::game_state::GameState;
+use shooting_game::input::Input;
+use shooting_game::key_bindings::KeyBindings;
+use shooting_game::position::Position;
+use shooting_game::simulation::{Simulation, CONTINUE_PENALTY_RATE};
+use shooting_game::stage::StageScript;
+use shooting_game::statistics::Statistics;
+use shooting_game::timestep::FixedTimestep;
+
+const WIDTH: f32 = 800.0;
+const HEIGHT: f32 = 600.0;
+const FRAME_TIME: Duration = Duration::from_millis(33); // 描画の間隔（約30fps、シミュレーションは60Hz のまま）
+
+// キーを離したことが分からない端末で、押しているとみなす時間
+const FIRST_HOLD: Duration = Duration::from_millis(500); // 押してからキーリピートが始まるまで
+const REPEAT_HOLD: Duration = Duration::from_millis(120); // キーリピートの間隔より少し長く
+
+type Rgb = [u8; 3];
+
+const OUTSIDE: Rgb = [0, 0, 0]; // プレイ画面の外
+const FIELD: Rgb = [8, 10, 24];
+const WHITE: Rgb = [255, 255, 255];
+const PLAYER: Rgb = [80, 200, 255];
+const PLAYER_BULLET: Rgb = [255, 240, 120];
+const ENEMY_BULLET: Rgb = [255, 100, 180];
+const EXPLOSION: Rgb = [255, 140, 40];
+const HEART: Rgb = [230, 40, 60];
+
+fn main() -> Result<()> {
+    let options = Options::parse(std::env::args().skip(1))?;
+    let terminal = TerminalGuard::enter()?;
+    run(options, terminal.reports_release)
+}
+
+// コマンドラインで選べる設定
+struct Options {
+    seed: u64,
+    config: GameConfig,
+    stage: StageScript, // なければ組み込みのステージ
+}
+
+impl Options {
+    fn parse(mut args: impl Iterator<Item = String>) -> Result<Options> {
+        let mut seed = None;
+        let mut config = GameConfig::default();
+        let mut stage = None;
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "--stage" => {
+                    let path = args.next().context("--stage needs a file")?;
+                    stage = Some(StageScript::from_file(&path)?);
+                }
+                "--escape-penalty" => {
+                    let value = args.next().context("--escape-penalty needs a number")?;
+                    config.escape_penalty = value.parse().context("--escape-penalty must be a non-negative integer")?;
+                }
+                _ if seed.is_none() => seed = Some(arg.parse().context("seed must be a number")?),
+                _ => bail!("unexpected argument \"{}\"", arg),
+            }
+        }
+        let seed = seed.unwrap_or_else(|| SystemTime::now().duration_since(UNIX_EPOCH).map_or(1, |d| d.as_nanos() as u64));
+        Ok(Options { seed, config, stage: stage.unwrap_or_default() })
+    }
+}
+
+fn run(options: Options, reports_release: bool) -> Result<()> {
+    let mut simulation = Simulation::with_stage(WIDTH, HEIGHT, options.seed, options.stage);
+    simulation.set_config(options.config);
+    let mut statistics = Statistics::default();
+    let key_bindings = KeyBindings::default();
+    let mut keys = HeldKeys::new(reports_release);
+    let mut timestep = FixedTimestep::default();
+    let mut screen = Screen::new(terminal::size()?);
+    let mut stdout = io::stdout();
+    let mut last = Instant::now();
+
+    loop {
+        // 次の描画の時刻まで入力を待つ
+        let deadline = last + FRAME_TIME;
+        while let Some(timeout) = deadline.checked_duration_since(Instant::now()) {
+            if !event::poll(timeout)? {
+                break;
+            }
+            match event::read()? {
+                Event::Key(key) if is_quit(&key) => return Ok(()),
+                Event::Key(key) => keys.handle(key, Instant::now()),
+                Event::Resize(columns, rows) => screen = Screen::new((columns, rows)),
+                _ => {}
+            }
+        }
+
+        let now = Instant::now();
+        let input = keys.input(&key_bindings);
+        for _ in 0..timestep.advance((now - last).as_secs_f64()) {
+            simulation.step(&input, timestep.dt());
+            for event in simulation.drain_events() {
+                if let GameEvent::GameStarted { .. } = event {
+                    statistics = Statistics::default();
+                }
+                statistics.record(&event);
+            }
+        }
+        // 離したキーは、少なくとも1回 step に渡してから消す（同じフレームで押して離しても効くように）
+        keys.expire(now);
+        last = now;
+
+        screen.draw(&simulation, &statistics, simulation.render_alpha(timestep.alpha()));
+        screen.present(&mut stdout)?;
+    }
+}
+
+// Q か Ctrl+C で終了する（raw モードでは Ctrl+C がシグナルにならない）
+fn is_quit(key: &KeyEvent) -> bool {
+    key.kind != KeyEventKind::Release
+        && match key.code {
+            KeyCode::Char('q') | KeyCode::Char('Q') => true,
+            KeyCode::Char('c') => key.modifiers.contains(KeyModifiers::CONTROL),
+            _ => false,
+        }
+}
+
+// raw モードと代替画面に切り替え、終わったら（パニックしても）元に戻す
+struct TerminalGuard {
+    reports_release: bool, // キーを離したことを知らせてくれる端末かどうか
+}
+
+impl TerminalGuard {
+    fn enter() -> Result<TerminalGuard> {
+        terminal::enable_raw_mode().context("failed to enable raw mode (is stdin a terminal?)")?;
+        let reports_release = terminal::supports_keyboard_enhancement().unwrap_or(false);
+        let guard = TerminalGuard { reports_release };
+
+        let mut stdout = io::stdout();
+        execute!(stdout, EnterAlternateScreen, Hide, Clear(ClearType::All))?;
+        if reports_release {
+            execute!(stdout, PushKeyboardEnhancementFlags(KeyboardEnhancementFlags::REPORT_EVENT_TYPES))?;
+        }
+
+        // パニックのメッセージが代替画面と一緒に消えないよう、先に端末を戻してから表示する
+        let hook = std::panic::take_hook();
+        std::panic::set_hook(Box::new(move |info| {
+            restore_terminal(reports_release);
+            hook(info);
+        }));
+        Ok(guard)
+    }
+}
+
+impl Drop for TerminalGuard {
+    fn drop(&mut self) {
+        restore_terminal(self.reports_release);
+    }
+}
+
+fn restore_terminal(reports_release: bool) {
+    let mut stdout = io::stdout();
+    if reports_release {
+        let _ = execute!(stdout, PopKeyboardEnhancementFlags);
+    }
+    let _ = execute!(stdout, ResetColor, Show, LeaveAlternateScreen);
+    let _ = terminal::disable_raw_mode();
+}
+
+// 押されているキーと、押しているとみなす期限（None は離すまで）
+struct HeldKeys {
+    keys: Vec<(String, Option<Instant>)>,
+    reports_release: bool,
+}
+
+impl HeldKeys {
+    fn new(reports_release: bool) -> HeldKeys {
+        HeldKeys { keys: Vec::new(), reports_release }
+    }
+
+    fn handle(&mut self, event: KeyEvent, now: Instant) {
+        let Some(name) = key_name(event.code) else {
+            return;
+        };
+        let index = self.keys.iter().position(|(key, _)| *key == name);
+        let deadline = match event.kind {
+            KeyEventKind::Release => Some(now),
+            _ if self.reports_release => None,
+            // 離したことが分からないので、最初はキーリピートが始まるまで、その後は次のリピートまで押しているとみなす
+            _ if index.is_none() => Some(now + FIRST_HOLD),
+            _ => Some(now + REPEAT_HOLD),
+        };
+        match index {
+            Some(i) => {
+                let held = &mut self.keys[i].1;
+                *held = match (*held, deadline) {
+                    (Some(held), Some(deadline)) if event.kind != KeyEventKind::Release => Some(held.max(deadline)),
+                    _ => deadline,
+                };
+            }
+            None if event.kind != KeyEventKind::Release => self.keys.push((name, deadline)),
+            None => {}
+        }
+    }
+
+    fn expire(&mut self, now: Instant) {
+        self.keys.retain(|(_, deadline)| deadline.is_none_or(|deadline| deadline > now));
+    }
+
+    fn input(&self, key_bindings: &KeyBindings) -> Input {
+        let names: Vec<String> = self.keys.iter().map(|(key, _)| key.clone()).collect();
+        key_bindings.input(&names)
+    }
+}
+
+// KeyBindings で使うキーの名前（ブラウザの KeyboardEvent.key と同じ）
+fn key_name(code: KeyCode) -> Option<String> {
+    let name = match code {
+        KeyCode::Left => "ArrowLeft",
+        KeyCode::Right => "ArrowRight",
+        KeyCode::Up => "ArrowUp",
+        KeyCode::Down => "ArrowDown",
+        KeyCode::Enter => "Enter",
+        KeyCode::Esc => "Escape",
+        KeyCode::Char(c) => return Some(c.to_string()),
+        _ => return None,
+    };
+    Some(name.to_string())
+}
+
+// 端末に出す1文字分
+#[derive(Clone, Copy, PartialEq)]
+struct Cell {
+    symbol: char,
+    foreground: Rgb,
+    background: Rgb,
+}
+
+// 1文字を縦2ピクセルとして描く画面
+// 前回端末に出した内容を覚えておき、変わった文字だけを送る（SSH 越しでも重くならないように）
+struct Screen {
+    columns: u16,
+    rows: u16,
+    pixels: Vec<Rgb>,                // columns × rows * 2
+    text: Vec<Option<(char, Rgb)>>, // ピクセルの上に重ねる文字（columns × rows）
+    shown: Vec<Option<Cell>>,       // 端末に今出ている文字（None はまだ出していない）
+    scale: f64,                      // ゲームの座標1あたりのピクセル数
+    left: f64,                       // プレイ画面の左上のピクセル位置
+    top: f64,
+}
+
+impl Screen {
+    fn new((columns, rows): (u16, u16)) -> Screen {
+        let (width, height) = (columns as f64, rows as f64 * 2.0);
+        let scale = (width / WIDTH as f64).min(height / HEIGHT as f64);
+        // 上端を文字の境目に揃えて、HUD の行がプレイ画面の下端に来るようにする
+        let top = ((height - HEIGHT as f64 * scale) / 4.0).floor() * 2.0;
+        let cells = columns as usize * rows as usize;
+        Screen {
+            columns,
+            rows,
+            pixels: vec![OUTSIDE; cells * 2],
+            text: vec![None; cells],
+            shown: vec![None; cells],
+            scale,
+            left: ((width - WIDTH as f64 * scale) / 2.0).floor(),
+            top,
+        }
+    }
+
+    // 今の状態の画面を描く（Renderer::render と同じ順に重ねる）
+    fn draw(&mut self, simulation: &Simulation, statistics: &Statistics, alpha: f32) {
+        self.pixels.fill(OUTSIDE);
+        self.text.fill(None);
+        self.fill_rect(0.0, 0.0, WIDTH as f64, HEIGHT as f64, FIELD);
+
+        // 画像の層は描けないので星空の層だけを描く
+        for layer in simulation.background().layers() {
+            if let LayerKind::Starfield { .. } = layer.layer.kind {
+                let distance = layer.interpolated_distance(alpha);
+                for star in &layer.stars {
+                    let y = (star.y as f64 + distance).rem_euclid(HEIGHT as f64);
+                    self.blend_pixel(self.to_pixel_x(star.x as f64), self.to_pixel_y(y), WHITE, star.brightness * 0.8);
+                }
+            }
+        }
+
+        let state = simulation.state();
+        if state == GameState::Loading {
+            return;
+        }
+        if state.shows_playfield() {
+            self.draw_playfield(simulation, alpha);
+        }
+
+        let y = HEIGHT as f64 / 2.0;
+        match state {
+            GameState::Loading | GameState::Playing => {}
+            GameState::Title => {
+                self.dim(0.4);
+                self.text_centered("SHOOTING GAME", y - 60.0, WHITE);
+                self.text_centered("Press Enter to start", y, WHITE);
+                self.text_centered("Arrows: move  Space: fire  1-4: weapon  Esc: pause  Q: quit", y + 60.0, WHITE);
+            }
+            GameState::Paused => {
+                self.dim(0.5);
+                self.text_centered("PAUSED", y, WHITE);
+                self.text_centered("Press Esc or Enter to resume", y + 50.0, WHITE);
+            }
+            GameState::StageClear => {
+                self.dim(0.3);
+                self.text_centered("STAGE CLEAR", y, WHITE);
+            }
+            GameState::GameOver => {
+                self.dim(0.5);
+                self.text_centered("GAME OVER", y, WHITE);
+                self.continue_hint(simulation, y + 50.0);
+            }
+            GameState::Results => {
+                self.dim(0.6);
+                self.text_centered("RESULTS", y - 120.0, WHITE);
+                self.text_centered(&format!("Score: {}", simulation.score()), y - 70.0, WHITE);
+                self.text_centered(&format!("Destroyed: {}", statistics.enemies_destroyed), y - 30.0, WHITE);
+                self.text_centered(&format!("Escaped: {}", statistics.enemies_escaped), y, WHITE);
+                self.text_centered(&format!("Accuracy: {:.0}%", statistics.accuracy() * 100.0), y + 30.0, WHITE);
+                self.text_centered("Press Enter to play again", y + 90.0, WHITE);
+                self.continue_hint(simulation, y + 130.0);
+            }
+        }
+    }
+
+    // 自機・弾・敵は当たり判定の形で描く（見た目の画像より小さいので、避けられるかどうかが分かりやすい）
+    fn draw_playfield(&mut self, simulation: &Simulation, alpha: f32) {
+        let player = simulation.player();
+        let position = player.interpolated_position(alpha);
+        self.fill_hitbox(player.get_hitbox(), position, PLAYER);
+        self.fill_hitbox(player.get_core_hitbox(), position, WHITE);
+
+        for bullet in simulation.bullets().values() {
+            self.fill_hitbox(bullet.get_hitbox(), bullet.interpolated_position(alpha), PLAYER_BULLET);
+        }
+        for enemy in simulation.enemies().values() {
+            let color = match enemy.get_enemy_type() {
+                EnemyType::Regular => [230, 70, 60],
+                EnemyType::Fast => [255, 170, 40],
+                EnemyType::Strong => [200, 90, 255],
+            };
+            self.fill_hitbox(enemy.get_hitbox(), enemy.interpolated_position(alpha), color);
+        }
+        for explosion in simulation.explosions().values() {
+            let radius = explosion.size as f64 * 0.3;
+            self.fill_ellipse(explosion.position.x as f64, explosion.position.y as f64, radius, radius, EXPLOSION);
+        }
+        for particle in simulation.particles().particles() {
+            let x = self.to_pixel_x(particle.position.x as f64);
+            let y = self.to_pixel_y(particle.position.y as f64);
+            self.add_pixel(x, y, particle.color(), particle.alpha());
+        }
+        for bullet in simulation.enemy_bullets().values() {
+            self.fill_hitbox(bullet.get_hitbox(), bullet.interpolated_position(alpha), ENEMY_BULLET);
+        }
+
+        self.draw_score(simulation.score());
+        self.draw_life(player.get_life());
+    }
+
+    // Renderer::draw_score と同じく左下にスコアを出す
+    fn draw_score(&mut self, score: u32) {
+        let row = self.bottom_row();
+        self.put_text(self.left as i64 + 1, row, &format!("Score: {}", score), WHITE);
+    }
+
+    // Renderer::draw_life と同じく右下から左へライフの数だけハートを並べる
+    fn draw_life(&mut self, life: u32) {
+        let row = self.bottom_row();
+        let right = (self.left + WIDTH as f64 * self.scale) as i64;
+        for i in 0..life as i64 {
+            self.put_text(right - 2 * (i + 1), row, "♥", HEART);
+        }
+    }
+
+    fn continue_hint(&mut self, simulation: &Simulation, y: f64) {
+        if simulation.can_continue() {
+            let penalty = (CONTINUE_PENALTY_RATE * 100.0).round();
+            self.text_centered(&format!("Press C to continue (score -{}%)", penalty), y, WHITE);
+        }
+    }
+
+    // プレイ画面の一番下の行
+    fn bottom_row(&self) -> i64 {
+        ((self.top + HEIGHT as f64 * self.scale) / 2.0).ceil() as i64 - 1
+    }
+
+    fn to_pixel_x(&self, x: f64) -> f64 {
+        self.left + x * self.scale
+    }
+
+    fn to_pixel_y(&self, y: f64) -> f64 {
+        self.top + y * self.scale
+    }
+
+    // (x, y) のピクセル（プレイ画面の外なら None、canvas の端で切れるのと同じ）
+    fn pixel_mut(&mut self, x: f64, y: f64) -> Option<&mut Rgb> {
+        let (x, y) = (x.floor(), y.floor());
+        let right = (self.left + WIDTH as f64 * self.scale).round().min(self.columns as f64);
+        let bottom = (self.top + HEIGHT as f64 * self.scale).round().min(self.rows as f64 * 2.0);
+        if x < self.left.max(0.0) || y < self.top.max(0.0) || x >= right || y >= bottom {
+            return None;
+        }
+        self.pixels.get_mut(y as usize * self.columns as usize + x as usize)
+    }
+
+    fn blend_pixel(&mut self, x: f64, y: f64, color: Rgb, alpha: f32) {
+        if let Some(pixel) = self.pixel_mut(x, y) {
+            let alpha = alpha.clamp(0.0, 1.0);
+            for i in 0..3 {
+                pixel[i] = (color[i] as f32 * alpha + pixel[i] as f32 * (1.0 - alpha)).round() as u8;
+            }
+        }
+    }
+
+    // パーティクルは光って見えるように下の色に足す
+    fn add_pixel(&mut self, x: f64, y: f64, color: Rgb, alpha: f32) {
+        if let Some(pixel) = self.pixel_mut(x, y) {
+            for i in 0..3 {
+                pixel[i] = (pixel[i] as f32 + color[i] as f32 * alpha.clamp(0.0, 1.0)).min(255.0) as u8;
+            }
+        }
+    }
+
+    // ゲームの座標で指定した長方形を塗る
+    fn fill_rect(&mut self, x: f64, y: f64, width: f64, height: f64, color: Rgb) {
+        let (x0, x1) = (self.to_pixel_x(x).round(), self.to_pixel_x(x + width).round());
+        let (y0, y1) = (self.to_pixel_y(y).round(), self.to_pixel_y(y + height).round());
+        for py in y0 as i64..y1 as i64 {
+            for px in x0 as i64..x1 as i64 {
+                if let Some(pixel) = self.pixel_mut(px as f64, py as f64) {
+                    *pixel = color;
+                }
+            }
+        }
+    }
+
+    fn fill_hitbox(&mut self, hitbox: &Hitbox, position: Position, color: Rgb) {
+        let center = hitbox.center(position);
+        let (half_width, half_height) = hitbox.half_extents();
+        self.fill_ellipse(center.x as f64, center.y as f64, half_width as f64, half_height as f64, color);
+    }
+
+    // ゲームの座標で指定した楕円を塗る（1ピクセルより小さくても中心の1ピクセルは塗る）
+    fn fill_ellipse(&mut self, x: f64, y: f64, half_width: f64, half_height: f64, color: Rgb) {
+        let (cx, cy) = (self.to_pixel_x(x), self.to_pixel_y(y));
+        let (rx, ry) = (half_width * self.scale, half_height * self.scale);
+        if let Some(pixel) = self.pixel_mut(cx, cy) {
+            *pixel = color;
+        }
+        for py in (cy - ry).floor() as i64..=(cy + ry).ceil() as i64 {
+            for px in (cx - rx).floor() as i64..=(cx + rx).ceil() as i64 {
+                let dx = (px as f64 + 0.5 - cx) / rx.max(f64::EPSILON);
+                let dy = (py as f64 + 0.5 - cy) / ry.max(f64::EPSILON);
+                if dx * dx + dy * dy <= 1.0 {
+                    if let Some(pixel) = self.pixel_mut(px as f64, py as f64) {
+                        *pixel = color;
+                    }
+                }
+            }
+        }
+    }
+
+    // 文字が読みやすいよう画面全体を暗くする
+    fn dim(&mut self, amount: f32) {
+        for pixel in &mut self.pixels {
+            for channel in pixel.iter_mut() {
+                *channel = (*channel as f32 * (1.0 - amount)).round() as u8;
+            }
+        }
+    }
+
+    fn put_text(&mut self, column: i64, row: i64, text: &str, color: Rgb) {
+        if row < 0 || row >= self.rows as i64 {
+            return;
+        }
+        for (i, c) in text.chars().enumerate() {
+            let x = column + i as i64;
+            if x >= 0 && x < self.columns as i64 {
+                self.text[row as usize * self.columns as usize + x as usize] = Some((c, color));
+            }
+        }
+    }
+
+    // ゲームの座標の高さ y の行に、プレイ画面の中央揃えで文字を出す
+    fn text_centered(&mut self, text: &str, y: f64, color: Rgb) {
+        let center = self.left + WIDTH as f64 * self.scale / 2.0;
+        let column = (center - text.chars().count() as f64 / 2.0).round() as i64;
+        let row = (self.to_pixel_y(y) / 2.0).floor() as i64;
+        self.put_text(column, row, text, color);
+    }
+
+    fn cell(&self, index: usize) -> Cell {
+        let columns = self.columns as usize;
+        let (row, column) = (index / columns, index % columns);
+        let upper = self.pixels[row * 2 * columns + column];
+        let lower = self.pixels[(row * 2 + 1) * columns + column];
+        match self.text[index] {
+            Some((symbol, color)) => Cell { symbol, foreground: color, background: lower },
+            None => Cell { symbol: '▀', foreground: upper, background: lower },
+        }
+    }
+
+    // 前回から変わった文字だけを端末に送る
+    fn present(&mut self, out: &mut impl Write) -> Result<()> {
+        let mut buffer = Vec::new();
+        let mut cursor = None;
+        let (mut foreground, mut background) = (None, None);
+        for index in 0..self.shown.len() {
+            let cell = self.cell(index);
+            if self.shown[index] == Some(cell) {
+                continue;
+            }
+            self.shown[index] = Some(cell);
+
+            let position = ((index % self.columns as usize) as u16, (index / self.columns as usize) as u16);
+            if cursor != Some(position) {
+                queue!(buffer, MoveTo(position.0, position.1))?;
+            }
+            if foreground != Some(cell.foreground) {
+                let [r, g, b] = cell.foreground;
+                queue!(buffer, SetForegroundColor(Color::Rgb { r, g, b }))?;
+                foreground = Some(cell.foreground);
+            }
+            if background != Some(cell.background) {
+                let [r, g, b] = cell.background;
+                queue!(buffer, SetBackgroundColor(Color::Rgb { r, g, b }))?;
+                background = Some(cell.background);
+            }
+            queue!(buffer, Print(cell.symbol))?;
+            cursor = Some((position.0 + 1, position.1));
+        }
+        if !buffer.is_empty() {
+            queue!(buffer, ResetColor)?;
+            out.write_all(&buffer)?;
+            out.flush()?;
+        }
+        Ok(())
+    }
+}
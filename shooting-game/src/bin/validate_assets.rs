@@ -0,0 +1,62 @@
+// 素材の一覧（assets.json）にあるファイルを実際に開いて確かめるツール
+//
+//   cargo run --bin validate_assets -- [assets.json]
+//
+// ファイルがあるか、画像として読めるか、宣言した大きさと合っているかを調べ、
+// 問題があれば一覧を表示して失敗で終わる
+use std::path::Path;
+use std::process::ExitCode;
+use image::GenericImageView;
+use shooting_game::asset_manifest::{AssetManifest, ImageAsset};
+
+fn main() -> ExitCode {
+    let manifest_path = std::env::args().nth(1).unwrap_or_else(|| "assets.json".to_string());
+
+    let manifest = match AssetManifest::from_file(&manifest_path) {
+        Ok(manifest) => manifest,
+        Err(err) => {
+            eprintln!("{}: {:#}", manifest_path, err);
+            return ExitCode::FAILURE;
+        }
+    };
+
+    // パスは一覧のファイルがあるディレクトリからの相対パス
+    let root = Path::new(&manifest_path).parent().unwrap_or(Path::new("."));
+
+    let mut failures = 0;
+    for image in &manifest.images {
+        match check_image(root, image) {
+            Ok(()) => println!("ok    {:<16} {}", image.name, image.path),
+            Err(problem) => {
+                failures += 1;
+                println!("FAIL  {:<16} {}: {}", image.name, image.path, problem);
+            }
+        }
+    }
+
+    println!();
+    if failures > 0 {
+        println!("{} of {} images have problems", failures, manifest.images.len());
+        ExitCode::FAILURE
+    } else {
+        println!("all {} images are valid", manifest.images.len());
+        ExitCode::SUCCESS
+    }
+}
+
+fn check_image(root: &Path, asset: &ImageAsset) -> Result<(), String> {
+    let path = root.join(&asset.path);
+    if !path.is_file() {
+        return Err("file not found".to_string());
+    }
+
+    let image = image::open(&path).map_err(|err| format!("cannot decode: {}", err))?;
+    let (width, height) = image.dimensions();
+    if (width, height) != (asset.width, asset.height) {
+        return Err(format!(
+            "declared {}x{} but the file is {}x{}",
+            asset.width, asset.height, width, height
+        ));
+    }
+    Ok(())
+}
@@ -1,24 +1,81 @@
 use crate::position::Position;
+use crate::collision::Hitbox;
+use crate::asset_manifest::{default_hitbox, AssetManifest};
 
 pub struct Bullet {
     pub position: Position,
+    pub previous_position: Position, // 1ステップ前の位置（描画の補間用）
     pub width: f32,
     pub height: f32,
-    velocity: u32
+    velocity_x: f32, // 1秒あたりの移動量
+    velocity_y: f32,
+    damage: u32,
+    sprite: &'static str,
+    hitbox: Hitbox,
 }
 
 impl Bullet {
+    // 通常の弾（真上に進む）
     pub fn new(x: f32, y: f32) -> Bullet {
-        Bullet { position: Position::new(x, y), width: 90.0, height: 90.0, velocity: 5}
+        Bullet::with_velocity(x, y, 0.0, -300.0)
     }
 
-    pub fn move_up(&mut self) {
-        self.position.y -= self.velocity as f32; // 弾が上に向かって進む
+    pub fn with_velocity(x: f32, y: f32, velocity_x: f32, velocity_y: f32) -> Bullet {
+        let position = Position::new(x, y);
+        Bullet {
+            position,
+            previous_position: position,
+            width: 90.0,
+            height: 90.0,
+            velocity_x,
+            velocity_y,
+            damage: 1,
+            sprite: "bullet",
+            hitbox: default_hitbox("bullet", 90.0, 90.0),
+        }
+    }
+
+    pub fn with_damage(mut self, damage: u32) -> Bullet {
+        self.damage = damage;
+        self
+    }
+
+    // 当たり判定も、その画像に書いたものがあれば大きさに合わせて置き換える
+    pub fn with_sprite(mut self, sprite: &'static str, width: f32, height: f32) -> Bullet {
+        self.sprite = sprite;
+        self.width = width;
+        self.height = height;
+        if let Some(hitbox) = AssetManifest::built_in().hitbox(sprite, width, height) {
+            self.hitbox = hitbox;
+        }
+        self
+    }
+
+    pub fn update(&mut self, dt: f32) {
+        self.previous_position = self.position;
+        self.position.x += self.velocity_x * dt;
+        self.position.y += self.velocity_y * dt;
     }
 
        // 配列として位置情報を返す
     pub fn get_position(&self) -> Position {
-        self.position.clone()
+        self.position
     }
-}
 
+    pub fn get_hitbox(&self) -> &Hitbox {
+        &self.hitbox
+    }
+
+    pub fn get_damage(&self) -> u32 {
+        self.damage
+    }
+
+    pub fn get_sprite(&self) -> &'static str {
+        self.sprite
+    }
+
+    // 前のステップと現在の位置を alpha で補間した描画用の位置
+    pub fn interpolated_position(&self, alpha: f32) -> Position {
+        self.previous_position.lerp(&self.position, alpha)
+    }
+}
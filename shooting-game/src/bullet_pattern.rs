@@ -0,0 +1,244 @@
+use std::collections::HashMap;
+use serde::Deserialize;
+use crate::bullet::Bullet;
+use crate::position::Position;
+use crate::rng::Rng;
+
+// 敵の弾の撃ち方
+// 角度は度数法で、0度が真下、90度が右、-90度が左
+#[derive(Clone, Deserialize, Debug)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum BulletPattern {
+    // プレイヤーを狙って1発
+    Aimed { speed: f32 },
+    // 全方位に等間隔で count 発
+    Ring {
+        count: u32,
+        speed: f32,
+        #[serde(default)]
+        offset: f32,
+    },
+    // 撃つたびに rotation 度ずつ回転する arms 本の弾
+    Spiral {
+        arms: u32,
+        speed: f32,
+        rotation: f32,
+    },
+    // spread 度の扇形に count 発（aimed なら中心をプレイヤーに向ける）
+    Fan {
+        count: u32,
+        speed: f32,
+        spread: f32,
+        #[serde(default)]
+        aimed: bool,
+    },
+    // プレイヤーを狙って、最大 spread 度ぶれる弾を1発
+    Stream { speed: f32, spread: f32 },
+    // 複数の撃ち方を同時に撃つ
+    Composite { patterns: Vec<BulletPattern> },
+}
+
+impl BulletPattern {
+    // origin から target に向けて撃つ弾を out に加える
+    // shot はこの撃ち方で何回目の発射か（回転する撃ち方で使う）
+    pub fn spawn(&self, origin: Position, target: Position, shot: u32, rng: &mut Rng, out: &mut impl Extend<Bullet>) {
+        let aim = (target.x - origin.x).atan2(target.y - origin.y).to_degrees();
+
+        match self {
+            BulletPattern::Aimed { speed } => out.extend([enemy_bullet(origin, aim, *speed)]),
+            BulletPattern::Ring { count, speed, offset } => out.extend(
+                (0..*count).map(|i| enemy_bullet(origin, offset + 360.0 * i as f32 / *count as f32, *speed)),
+            ),
+            BulletPattern::Spiral { arms, speed, rotation } => out.extend((0..*arms).map(|i| {
+                let angle = rotation * shot as f32 + 360.0 * i as f32 / *arms as f32;
+                enemy_bullet(origin, angle, *speed)
+            })),
+            BulletPattern::Fan { count, speed, spread, aimed } => {
+                let center = if *aimed { aim } else { 0.0 };
+                out.extend((0..*count).map(|i| {
+                    let t = if *count > 1 { i as f32 / (*count - 1) as f32 - 0.5 } else { 0.0 };
+                    enemy_bullet(origin, center + spread * t, *speed)
+                }));
+            }
+            BulletPattern::Stream { speed, spread } => {
+                let angle = aim + rng.range(-spread / 2.0, spread / 2.0);
+                out.extend([enemy_bullet(origin, angle, *speed)]);
+            }
+            BulletPattern::Composite { patterns } => {
+                for pattern in patterns {
+                    pattern.spawn(origin, target, shot, rng, out);
+                }
+            }
+        }
+    }
+}
+
+fn enemy_bullet(origin: Position, angle: f32, speed: f32) -> Bullet {
+    let angle = angle.to_radians();
+    Bullet::with_velocity(origin.x, origin.y, speed * angle.sin(), speed * angle.cos())
+        .with_sprite("enemy_bullet", 24.0, 24.0)
+}
+
+// 名前で参照できる撃ち方の一覧
+#[derive(Clone)]
+pub struct PatternLibrary {
+    patterns: HashMap<String, BulletPattern>,
+}
+
+impl PatternLibrary {
+    pub fn get(&self, name: &str) -> Option<&BulletPattern> {
+        self.patterns.get(name)
+    }
+
+    pub fn insert(&mut self, name: &str, pattern: BulletPattern) {
+        self.patterns.insert(name.to_string(), pattern);
+    }
+}
+
+impl Default for PatternLibrary {
+    // 組み込みの撃ち方
+    fn default() -> PatternLibrary {
+        let mut library = PatternLibrary { patterns: HashMap::new() };
+        library.insert("aimed", BulletPattern::Aimed { speed: 180.0 });
+        library.insert("ring", BulletPattern::Ring { count: 12, speed: 120.0, offset: 0.0 });
+        library.insert("spiral", BulletPattern::Spiral { arms: 3, speed: 140.0, rotation: 17.0 });
+        library.insert("fan", BulletPattern::Fan { count: 5, speed: 160.0, spread: 60.0, aimed: true });
+        library.insert("stream", BulletPattern::Stream { speed: 220.0, spread: 20.0 });
+        library
+    }
+}
+
+// 敵が弾を撃つタイミングを管理する
+#[derive(Clone)]
+pub struct Emitter {
+    pattern: BulletPattern,
+    interval: f32, // 発射間隔（秒単位）
+    timer: f32,    // 次の発射までの残り時間
+    shots: u32,    // これまでに撃った回数
+}
+
+impl Emitter {
+    pub fn new(pattern: BulletPattern, interval: f32) -> Emitter {
+        Emitter { pattern, interval, timer: interval, shots: 0 }
+    }
+
+    // 時間を進め、発射するタイミングになったら撃った弾を out に加える
+    pub fn update(&mut self, dt: f32, origin: Position, target: Position, rng: &mut Rng, out: &mut impl Extend<Bullet>) {
+        self.timer -= dt;
+        if self.timer > 0.0 {
+            return;
+        }
+
+        self.timer += self.interval;
+        self.pattern.spawn(origin, target, self.shots, rng, out);
+        self.shots += 1;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ORIGIN: Position = Position { x: 400.0, y: 100.0 };
+    const BELOW_RIGHT: Position = Position { x: 500.0, y: 200.0 }; // ORIGIN から見て 45度
+
+    fn spawn(pattern: &BulletPattern, shot: u32) -> Vec<Bullet> {
+        let mut bullets = Vec::new();
+        pattern.spawn(ORIGIN, BELOW_RIGHT, shot, &mut Rng::new(1), &mut bullets);
+        bullets
+    }
+
+    // 弾の進む向き（度数法、0度が真下）と速さ
+    fn headings(bullets: &mut [Bullet]) -> Vec<(f32, f32)> {
+        bullets
+            .iter_mut()
+            .map(|bullet| {
+                let start = bullet.get_position();
+                bullet.update(1.0);
+                let (dx, dy) = (bullet.get_position().x - start.x, bullet.get_position().y - start.y);
+                (dx.atan2(dy).to_degrees(), dx.hypot(dy))
+            })
+            .collect()
+    }
+
+    fn assert_angles(actual: &[(f32, f32)], expected: &[f32]) {
+        assert_eq!(actual.len(), expected.len(), "{:?}", actual);
+        for ((angle, _), expected) in actual.iter().zip(expected) {
+            // -180度と180度は同じ向き
+            let diff = (angle - expected).rem_euclid(360.0);
+            assert!(diff.min(360.0 - diff) < 0.01, "{:?} != {:?}", actual, expected);
+        }
+    }
+
+    #[test]
+    fn aimed_shoots_at_the_target() {
+        let headings = headings(&mut spawn(&BulletPattern::Aimed { speed: 180.0 }, 0));
+        assert_angles(&headings, &[45.0]);
+        assert!((headings[0].1 - 180.0).abs() < 0.01);
+    }
+
+    #[test]
+    fn ring_and_spiral_spread_evenly() {
+        let ring = BulletPattern::Ring { count: 4, speed: 100.0, offset: 10.0 };
+        assert_angles(&headings(&mut spawn(&ring, 0)), &[10.0, 100.0, 190.0, 280.0]);
+
+        let spiral = BulletPattern::Spiral { arms: 3, speed: 100.0, rotation: 15.0 };
+        assert_angles(&headings(&mut spawn(&spiral, 0)), &[0.0, 120.0, 240.0]);
+        assert_angles(&headings(&mut spawn(&spiral, 2)), &[30.0, 150.0, 270.0]);
+    }
+
+    #[test]
+    fn fan_spreads_around_its_center() {
+        let fan = BulletPattern::Fan { count: 5, speed: 100.0, spread: 60.0, aimed: false };
+        assert_angles(&headings(&mut spawn(&fan, 0)), &[-30.0, -15.0, 0.0, 15.0, 30.0]);
+
+        let aimed = BulletPattern::Fan { count: 3, speed: 100.0, spread: 40.0, aimed: true };
+        assert_angles(&headings(&mut spawn(&aimed, 0)), &[25.0, 45.0, 65.0]);
+
+        let single = BulletPattern::Fan { count: 1, speed: 100.0, spread: 40.0, aimed: true };
+        assert_angles(&headings(&mut spawn(&single, 0)), &[45.0]);
+    }
+
+    #[test]
+    fn stream_wobbles_within_its_spread() {
+        let stream = BulletPattern::Stream { speed: 100.0, spread: 20.0 };
+        let mut rng = Rng::new(3);
+        let mut bullets = Vec::new();
+        for _ in 0..200 {
+            stream.spawn(ORIGIN, BELOW_RIGHT, 0, &mut rng, &mut bullets);
+        }
+        let angles: Vec<f32> = headings(&mut bullets).iter().map(|(angle, _)| *angle).collect();
+        assert!(angles.iter().all(|angle| (35.0..55.0).contains(angle)), "{:?}", angles);
+        assert!(angles.iter().any(|angle| *angle < 40.0) && angles.iter().any(|angle| *angle > 50.0));
+    }
+
+    #[test]
+    fn composite_fires_every_pattern() {
+        let composite = BulletPattern::Composite {
+            patterns: vec![
+                BulletPattern::Aimed { speed: 100.0 },
+                BulletPattern::Ring { count: 6, speed: 100.0, offset: 0.0 },
+            ],
+        };
+        assert_eq!(spawn(&composite, 0).len(), 7);
+    }
+
+    #[test]
+    fn emitter_fires_once_per_interval() {
+        let mut emitter = Emitter::new(BulletPattern::Spiral { arms: 1, speed: 100.0, rotation: 90.0 }, 0.5);
+        let mut rng = Rng::new(1);
+        let mut bullets = Vec::new();
+        let mut fired_at = Vec::new();
+        // 0.05 秒刻みで 2.05 秒進める（最初の発射は1間隔たってから）
+        for step in 1..=41 {
+            let before = bullets.len();
+            emitter.update(0.05, ORIGIN, BELOW_RIGHT, &mut rng, &mut bullets);
+            if bullets.len() > before {
+                fired_at.push(step);
+            }
+        }
+        assert_eq!(fired_at, [10, 20, 30, 40]);
+        // 撃つたびに回転が進む
+        assert_angles(&headings(&mut bullets), &[0.0, 90.0, 180.0, 270.0]);
+    }
+}
@@ -0,0 +1,63 @@
+use js_sys::Function;
+use wasm_bindgen::{JsError, JsValue};
+
+// ホストページに知らせる出来事
+pub enum HostEvent {
+    ScoreChanged(u32), // 新しいスコア
+    LifeLost(u32),     // 残りのライフ
+    GameOver(u32),     // 最終スコア
+    StageCleared(u32), // 最終スコア
+}
+
+impl HostEvent {
+    fn name(&self) -> &'static str {
+        match self {
+            HostEvent::ScoreChanged(_) => "scoreChanged",
+            HostEvent::LifeLost(_) => "lifeLost",
+            HostEvent::GameOver(_) => "gameOver",
+            HostEvent::StageCleared(_) => "stageCleared",
+        }
+    }
+
+    fn value(&self) -> u32 {
+        match *self {
+            HostEvent::ScoreChanged(value)
+            | HostEvent::LifeLost(value)
+            | HostEvent::GameOver(value)
+            | HostEvent::StageCleared(value) => value,
+        }
+    }
+}
+
+const EVENT_NAMES: [&str; 4] = ["scoreChanged", "lifeLost", "gameOver", "stageCleared"];
+
+// JavaScript から登録されたコールバック（出来事ごとに1つ）
+#[derive(Clone, Default)]
+pub struct Callbacks {
+    callbacks: Vec<(&'static str, Function)>,
+}
+
+impl Callbacks {
+    // callback が None なら登録を解除する
+    pub fn set(&mut self, event: &str, callback: Option<Function>) -> Result<(), JsValue> {
+        let name = EVENT_NAMES
+            .into_iter()
+            .find(|name| *name == event)
+            .ok_or_else(|| JsError::new(&format!("unknown event \"{}\"", event)))?;
+
+        self.callbacks.retain(|(n, _)| *n != name);
+        if let Some(callback) = callback {
+            self.callbacks.push((name, callback));
+        }
+        Ok(())
+    }
+
+    // コールバックの中で起きたエラーはゲームを止めずにコンソールに出す
+    pub fn dispatch(&self, event: &HostEvent) {
+        for (_, callback) in self.callbacks.iter().filter(|(name, _)| *name == event.name()) {
+            if let Err(err) = callback.call1(&JsValue::NULL, &event.value().into()) {
+                web_sys::console::error_1(&err);
+            }
+        }
+    }
+}
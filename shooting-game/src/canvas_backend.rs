@@ -0,0 +1,124 @@
+use wasm_bindgen::{JsCast, JsValue};
+use web_sys::{CanvasRenderingContext2d, HtmlCanvasElement};
+use crate::assets::Assets;
+use crate::render_backend::{BlendMode, Color, Dest, Paint, RenderBackend, TextAlign};
+use crate::sprite_atlas::Rect;
+
+// ブラウザの canvas に描く描画先
+// 画像は Assets から名前で引き、読み込みが終わっていないものは描かない
+pub struct CanvasBackend {
+    ctx: CanvasRenderingContext2d,
+    canvas: HtmlCanvasElement,
+    assets: Assets,
+}
+
+impl CanvasBackend {
+    pub fn new(canvas: HtmlCanvasElement, assets: Assets) -> Result<CanvasBackend, JsValue> {
+        let ctx = canvas
+            .get_context("2d")?
+            .ok_or_else(|| JsValue::from_str("canvas has no 2d context"))?
+            .dyn_into::<CanvasRenderingContext2d>()?;
+
+        Ok(CanvasBackend { ctx, canvas, assets })
+    }
+
+    pub fn assets(&self) -> &Assets {
+        &self.assets
+    }
+
+    // 図形の輪郭を描いたあとに塗るか線を引く
+    fn paint(&self, paint: Paint) {
+        match paint {
+            Paint::Fill(color) => {
+                self.ctx.set_fill_style_str(&color.to_css());
+                self.ctx.fill();
+            }
+            Paint::Stroke(color, width) => {
+                self.ctx.set_stroke_style_str(&color.to_css());
+                self.ctx.set_line_width(width);
+                self.ctx.stroke();
+            }
+        }
+    }
+}
+
+impl RenderBackend for CanvasBackend {
+    fn width(&self) -> f64 {
+        self.canvas.width() as f64
+    }
+
+    fn height(&self) -> f64 {
+        self.canvas.height() as f64
+    }
+
+    fn clear(&mut self, color: Color) {
+        self.ctx.clear_rect(0.0, 0.0, self.width(), self.height());
+        self.ctx.set_fill_style_str(&color.to_css());
+        self.ctx.fill_rect(0.0, 0.0, self.width(), self.height());
+    }
+
+    fn draw_sprite(&mut self, image: &str, source: Rect, dest: Dest, flip_y: bool) -> bool {
+        let Some(image) = self.assets.image(image) else {
+            return false;
+        };
+
+        self.ctx.save();
+        if flip_y {
+            self.ctx.translate(dest.x, dest.y + dest.height).unwrap();
+            self.ctx.scale(1.0, -1.0).unwrap();
+        } else {
+            self.ctx.translate(dest.x, dest.y).unwrap();
+        }
+        self.ctx.draw_image_with_html_image_element_and_sw_and_sh_and_dx_and_dy_and_dw_and_dh(
+            &image,
+            source.x as f64,
+            source.y as f64,
+            source.width as f64,
+            source.height as f64,
+            0.0,
+            0.0,
+            dest.width,
+            dest.height,
+        ).unwrap();
+        self.ctx.restore();
+        true
+    }
+
+    fn draw_rect(&mut self, x: f64, y: f64, width: f64, height: f64, paint: Paint) {
+        self.ctx.begin_path();
+        self.ctx.rect(x, y, width, height);
+        self.paint(paint);
+    }
+
+    fn draw_circle(&mut self, x: f64, y: f64, radius: f64, paint: Paint) {
+        self.ctx.begin_path();
+        self.ctx.arc(x, y, radius, 0.0, std::f64::consts::PI * 2.0).unwrap();
+        self.paint(paint);
+    }
+
+    fn draw_line(&mut self, from: (f64, f64), to: (f64, f64), color: Color, width: f64) {
+        self.ctx.begin_path();
+        self.ctx.move_to(from.0, from.1);
+        self.ctx.line_to(to.0, to.1);
+        self.paint(Paint::Stroke(color, width));
+    }
+
+    fn draw_text(&mut self, text: &str, x: f64, y: f64, size: u32, color: Color, align: TextAlign) {
+        self.ctx.set_font(&format!("{}px Arial", size));
+        self.ctx.set_fill_style_str(&color.to_css());
+        self.ctx.set_text_align(match align {
+            TextAlign::Left => "start",
+            TextAlign::Center => "center",
+        });
+        self.ctx.fill_text(text, x, y).unwrap();
+        self.ctx.set_text_align("start");
+    }
+
+    fn set_blend_mode(&mut self, mode: BlendMode) {
+        let operation = match mode {
+            BlendMode::Normal => "source-over",
+            BlendMode::Additive => "lighter",
+        };
+        let _ = self.ctx.set_global_composite_operation(operation);
+    }
+}
@@ -0,0 +1,244 @@
+use serde::Deserialize;
+use crate::position::Position;
+
+// 当たり判定の形
+// JSON では { "type": "circle", "radius": 12 } のように書く
+#[derive(Clone, Copy, Debug, PartialEq, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Shape {
+    // 円
+    Circle { radius: f32 },
+    // 軸に沿った長方形
+    Rect { width: f32, height: f32 },
+    // 縦向きのカプセル（長さ half_length * 2 の縦線分から radius 以内の範囲）
+    Capsule { radius: f32, half_length: f32 },
+}
+
+// エンティティの中心からずらして置いた当たり判定
+#[derive(Clone, Copy, Debug, PartialEq, Deserialize)]
+pub struct Hitbox {
+    #[serde(flatten)]
+    pub shape: Shape,
+    #[serde(default)]
+    pub offset_x: f32,
+    #[serde(default)]
+    pub offset_y: f32,
+}
+
+impl Hitbox {
+    pub fn circle(radius: f32) -> Hitbox {
+        Hitbox { shape: Shape::Circle { radius }, offset_x: 0.0, offset_y: 0.0 }
+    }
+
+    pub fn rect(width: f32, height: f32) -> Hitbox {
+        Hitbox { shape: Shape::Rect { width, height }, offset_x: 0.0, offset_y: 0.0 }
+    }
+
+    pub fn capsule(radius: f32, half_length: f32) -> Hitbox {
+        Hitbox { shape: Shape::Capsule { radius, half_length }, offset_x: 0.0, offset_y: 0.0 }
+    }
+
+    pub fn with_offset(mut self, offset_x: f32, offset_y: f32) -> Hitbox {
+        self.offset_x = offset_x;
+        self.offset_y = offset_y;
+        self
+    }
+
+    // 横に scale_x 倍、縦に scale_y 倍して描くときの当たり判定（円とカプセルの半径は横の倍率に合わせる）
+    pub fn scaled(&self, scale_x: f32, scale_y: f32) -> Hitbox {
+        let shape = match self.shape {
+            Shape::Circle { radius } => Shape::Circle { radius: radius * scale_x },
+            Shape::Rect { width, height } => Shape::Rect { width: width * scale_x, height: height * scale_y },
+            Shape::Capsule { radius, half_length } => Shape::Capsule {
+                radius: radius * scale_x,
+                half_length: half_length * scale_y,
+            },
+        };
+        Hitbox { shape, offset_x: self.offset_x * scale_x, offset_y: self.offset_y * scale_y }
+    }
+
+    // position にあるエンティティの当たり判定の中心
+    pub fn center(&self, position: Position) -> Position {
+        Position::new(position.x + self.offset_x, position.y + self.offset_y)
+    }
+
+    // 当たり判定を囲む長方形の幅と高さの半分
+    pub fn half_extents(&self) -> (f32, f32) {
+        match self.shape {
+            Shape::Circle { radius } => (radius, radius),
+            Shape::Rect { width, height } => (width / 2.0, height / 2.0),
+            Shape::Capsule { radius, half_length } => (radius, half_length + radius),
+        }
+    }
+
+    // position にある self と other_position にある other が重なっているか
+    pub fn intersects(&self, position: Position, other: &Hitbox, other_position: Position) -> bool {
+        let a = Primitive::new(self, position);
+        let b = Primitive::new(other, other_position);
+
+        match (a, b) {
+            (Primitive::Segment(a), Primitive::Segment(b)) => {
+                let dx = (a.x - b.x).abs();
+                let dy = interval_gap(a.top, a.bottom, b.top, b.bottom);
+                within(dx, dy, a.radius + b.radius)
+            }
+            (Primitive::Segment(s), Primitive::Box(r)) | (Primitive::Box(r), Primitive::Segment(s)) => {
+                let dx = interval_gap(s.x, s.x, r.left, r.right);
+                let dy = interval_gap(s.top, s.bottom, r.top, r.bottom);
+                within(dx, dy, s.radius)
+            }
+            (Primitive::Box(a), Primitive::Box(b)) => {
+                a.left < b.right && b.left < a.right && a.top < b.bottom && b.top < a.bottom
+            }
+        }
+    }
+}
+
+// 判定用に変換した形
+// 円は長さ0のカプセルとして扱うので、線分と長方形の組み合わせだけ考えればよい
+enum Primitive {
+    Segment(Segment),
+    Box(Bounds),
+}
+
+// 縦の線分とそこからの半径
+struct Segment {
+    x: f32,
+    top: f32,
+    bottom: f32,
+    radius: f32,
+}
+
+struct Bounds {
+    left: f32,
+    right: f32,
+    top: f32,
+    bottom: f32,
+}
+
+impl Primitive {
+    fn new(hitbox: &Hitbox, position: Position) -> Primitive {
+        let center = hitbox.center(position);
+        match hitbox.shape {
+            Shape::Circle { radius } => Primitive::Segment(Segment {
+                x: center.x,
+                top: center.y,
+                bottom: center.y,
+                radius,
+            }),
+            Shape::Capsule { radius, half_length } => Primitive::Segment(Segment {
+                x: center.x,
+                top: center.y - half_length,
+                bottom: center.y + half_length,
+                radius,
+            }),
+            Shape::Rect { width, height } => Primitive::Box(Bounds {
+                left: center.x - width / 2.0,
+                right: center.x + width / 2.0,
+                top: center.y - height / 2.0,
+                bottom: center.y + height / 2.0,
+            }),
+        }
+    }
+}
+
+// 区間 [a_min, a_max] と [b_min, b_max] の間の距離（重なっていれば0）
+fn interval_gap(a_min: f32, a_max: f32, b_min: f32, b_max: f32) -> f32 {
+    (b_min - a_max).max(a_min - b_max).max(0.0)
+}
+
+fn within(dx: f32, dy: f32, distance: f32) -> bool {
+    dx * dx + dy * dy < distance * distance
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(x: f32, y: f32) -> Position {
+        Position::new(x, y)
+    }
+
+    // どちらの順に判定しても同じ結果になることも確かめる
+    fn hits(a: Hitbox, a_position: Position, b: Hitbox, b_position: Position) -> bool {
+        let result = a.intersects(a_position, &b, b_position);
+        assert_eq!(result, b.intersects(b_position, &a, a_position), "intersects should be symmetric");
+        result
+    }
+
+    #[test]
+    fn circle_circle() {
+        let circle = Hitbox::circle(10.0);
+        assert!(hits(circle, at(0.0, 0.0), circle, at(19.0, 0.0)));
+        assert!(!hits(circle, at(0.0, 0.0), circle, at(21.0, 0.0)));
+        // 斜めの距離で判定する（外接する長方形は重なっている）
+        assert!(!hits(circle, at(0.0, 0.0), circle, at(15.0, 15.0)));
+    }
+
+    #[test]
+    fn circle_rect() {
+        let circle = Hitbox::circle(10.0);
+        let rect = Hitbox::rect(40.0, 20.0);
+        assert!(hits(circle, at(0.0, 0.0), rect, at(29.0, 0.0)));
+        assert!(!hits(circle, at(0.0, 0.0), rect, at(31.0, 0.0)));
+        assert!(hits(circle, at(0.0, 0.0), rect, at(0.0, 19.0)));
+        // 角の近く：外接する長方形は重なっているが、角までの距離は半径より遠い
+        assert!(!hits(circle, at(0.0, 0.0), rect, at(28.0, 18.0)));
+        assert!(hits(circle, at(0.0, 0.0), rect, at(25.0, 15.0)));
+    }
+
+    #[test]
+    fn circle_capsule() {
+        let circle = Hitbox::circle(5.0);
+        let capsule = Hitbox::capsule(5.0, 20.0);
+        // 縦の線分の横
+        assert!(hits(circle, at(9.0, 15.0), capsule, at(0.0, 0.0)));
+        assert!(!hits(circle, at(11.0, 15.0), capsule, at(0.0, 0.0)));
+        // 線分の端の丸い部分
+        assert!(hits(circle, at(0.0, 29.0), capsule, at(0.0, 0.0)));
+        assert!(!hits(circle, at(0.0, 31.0), capsule, at(0.0, 0.0)));
+        assert!(!hits(circle, at(8.0, 28.0), capsule, at(0.0, 0.0)));
+    }
+
+    #[test]
+    fn rect_rect() {
+        let a = Hitbox::rect(20.0, 10.0);
+        let b = Hitbox::rect(10.0, 10.0);
+        assert!(hits(a, at(0.0, 0.0), b, at(14.0, 0.0)));
+        assert!(!hits(a, at(0.0, 0.0), b, at(16.0, 0.0)));
+        assert!(hits(a, at(0.0, 0.0), b, at(14.0, 9.0)));
+        assert!(!hits(a, at(0.0, 0.0), b, at(14.0, 11.0)));
+        // 辺が接しているだけなら重なっていない
+        assert!(!hits(a, at(0.0, 0.0), b, at(15.0, 0.0)));
+    }
+
+    #[test]
+    fn rect_capsule() {
+        let rect = Hitbox::rect(20.0, 20.0);
+        let capsule = Hitbox::capsule(5.0, 20.0);
+        assert!(hits(rect, at(0.0, 0.0), capsule, at(14.0, 0.0)));
+        assert!(!hits(rect, at(0.0, 0.0), capsule, at(16.0, 0.0)));
+        // カプセルの下端の丸い部分が長方形の角に近づく
+        assert!(hits(rect, at(0.0, 0.0), capsule, at(12.0, -32.0)));
+        assert!(!hits(rect, at(0.0, 0.0), capsule, at(14.0, -34.0)));
+    }
+
+    #[test]
+    fn capsule_capsule() {
+        let capsule = Hitbox::capsule(5.0, 20.0);
+        assert!(hits(capsule, at(0.0, 0.0), capsule, at(9.0, 30.0)));
+        assert!(!hits(capsule, at(0.0, 0.0), capsule, at(11.0, 30.0)));
+        // 縦に並んだときは端の丸い部分どうしで判定する
+        assert!(hits(capsule, at(0.0, 0.0), capsule, at(0.0, 49.0)));
+        assert!(!hits(capsule, at(0.0, 0.0), capsule, at(0.0, 51.0)));
+    }
+
+    #[test]
+    fn offset_moves_the_hitbox() {
+        let shifted = Hitbox::circle(10.0).with_offset(0.0, 30.0);
+        let circle = Hitbox::circle(10.0);
+        assert!(hits(shifted, at(0.0, 0.0), circle, at(0.0, 45.0)));
+        assert!(!hits(shifted, at(0.0, 0.0), circle, at(0.0, 5.0)));
+        assert_eq!(shifted.center(at(1.0, 2.0)), at(1.0, 32.0));
+    }
+}
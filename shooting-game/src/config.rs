@@ -0,0 +1,56 @@
+use crate::player::START_LIFE;
+
+// 難易度
+#[derive(Clone, Copy, PartialEq, Debug, Default)]
+pub enum Difficulty {
+    Easy,
+    #[default]
+    Normal,
+    Hard,
+}
+
+impl Difficulty {
+    pub fn from_name(name: &str) -> Option<Difficulty> {
+        match name {
+            "easy" => Some(Difficulty::Easy),
+            "normal" => Some(Difficulty::Normal),
+            "hard" => Some(Difficulty::Hard),
+            _ => None,
+        }
+    }
+
+    // 敵の弾の発射間隔に掛ける倍率
+    pub fn fire_interval_scale(&self) -> f32 {
+        match self {
+            Difficulty::Easy => 1.5,
+            Difficulty::Normal => 1.0,
+            Difficulty::Hard => 0.6,
+        }
+    }
+
+    // 敵の移動速度に掛ける倍率
+    pub fn enemy_speed_scale(&self) -> f32 {
+        match self {
+            Difficulty::Easy => 0.8,
+            Difficulty::Normal => 1.0,
+            Difficulty::Hard => 1.25,
+        }
+    }
+}
+
+// 選べるライフの最大値（ライフのハートが画面の幅に収まる数）
+pub const MAX_STARTING_LIVES: u32 = 10;
+
+// ゲームを作るときに選べる設定
+#[derive(Clone, Copy, Debug)]
+pub struct GameConfig {
+    pub difficulty: Difficulty,
+    pub starting_lives: u32, // ゲーム開始時とコンティニュー時のライフ
+    pub escape_penalty: u32, // 敵を逃がしたときにスコアから引く点数（0なら減点しない）
+}
+
+impl Default for GameConfig {
+    fn default() -> GameConfig {
+        GameConfig { difficulty: Difficulty::Normal, starting_lives: START_LIFE, escape_penalty: 0 }
+    }
+}
@@ -0,0 +1,90 @@
+use std::collections::VecDeque;
+use crate::renderer::Renderer;
+use crate::render_backend::{Color, RenderBackend};
+use crate::simulation::Simulation;
+
+// グラフに残すフレームの数
+const HISTORY: usize = 120;
+
+// 1フレーム分の計測値（ミリ秒単位）
+#[derive(Clone, Copy)]
+pub struct FrameSample {
+    pub frame_time: f64, // 前のフレームからの経過時間
+    pub tick_time: f64,  // このフレームで Simulation のステップにかかった時間
+}
+
+// 当たり判定やグリッド、処理時間などを重ねて表示するデバッグ表示
+// デバッグビルドにだけ含まれ、リリースビルドでは切り替えても何も描かない
+#[derive(Default)]
+pub struct DebugOverlay {
+    enabled: bool,
+    samples: VecDeque<FrameSample>,
+}
+
+impl DebugOverlay {
+    pub fn is_enabled(&self) -> bool {
+        self.enabled
+    }
+
+    pub fn set_enabled(&mut self, enabled: bool) {
+        self.enabled = enabled;
+    }
+
+    pub fn record(&mut self, sample: FrameSample) {
+        if self.samples.len() == HISTORY {
+            self.samples.pop_front();
+        }
+        self.samples.push_back(sample);
+    }
+
+    pub fn draw<B: RenderBackend>(&self, renderer: &mut Renderer<B>, simulation: &Simulation) {
+        if !self.enabled {
+            return;
+        }
+
+        // 当たり判定は描画の補間をしない、判定に使う位置に描く
+        renderer.draw_debug_grid(simulation.enemy_grid());
+        let player = simulation.player();
+        renderer.draw_debug_hitbox(player.get_position(), player.get_hitbox(), Color::rgb(0, 255, 0));
+        renderer.draw_debug_hitbox(player.get_position(), player.get_core_hitbox(), Color::rgb(255, 255, 0));
+        for enemy in simulation.enemies().values() {
+            renderer.draw_debug_hitbox(enemy.get_position(), enemy.get_hitbox(), Color::RED);
+        }
+        for bullet in simulation.bullets().values() {
+            renderer.draw_debug_hitbox(bullet.get_position(), bullet.get_hitbox(), Color::rgb(0, 255, 255));
+        }
+        for bullet in simulation.enemy_bullets().values() {
+            renderer.draw_debug_hitbox(bullet.get_position(), bullet.get_hitbox(), Color::rgb(255, 0, 255));
+        }
+
+        let stage = simulation.stage();
+        let waves = stage.script().waves.len();
+        let wave = stage.script().waves.get(stage.wave_index());
+        let count = self.samples.len().max(1) as f64;
+        let frame_time = self.samples.iter().map(|sample| sample.frame_time).sum::<f64>() / count;
+        let tick_time = self.samples.iter().map(|sample| sample.tick_time).sum::<f64>() / count;
+        let lines = [
+            format!("FPS {:.0} ({:.1} ms)  tick {:.2} ms", 1000.0 / frame_time.max(0.001), frame_time, tick_time),
+            format!(
+                "enemies {}  bullets {}  enemy bullets {}",
+                simulation.enemies().len(),
+                simulation.bullets().len(),
+                simulation.enemy_bullets().len()
+            ),
+            format!("explosions {}  particles {}", simulation.explosions().len(), simulation.particles().len()),
+            format!(
+                "{}  wave {}/{}  spawned {}/{}  {:.1} s",
+                stage.script().name,
+                (stage.wave_index() + 1).min(waves),
+                waves,
+                stage.spawned(),
+                wave.map_or(0, |wave| wave.count),
+                stage.timer()
+            ),
+            format!("state {}  time {:.1} s  seed {}", simulation.state().name(), simulation.time(), simulation.seed()),
+        ];
+        renderer.draw_debug_text(&lines, 10.0, 20.0);
+        let samples = self.samples.iter().map(|sample| (sample.frame_time, sample.tick_time));
+        renderer.draw_debug_graph(samples, 10.0, 20.0 + lines.len() as f64 * 16.0);
+    }
+}
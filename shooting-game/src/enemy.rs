@@ -1,23 +1,107 @@
 use crate::position::Position;
+use crate::enemy_type::EnemyType;
+use crate::bullet::Bullet;
+use crate::bullet_pattern::Emitter;
+use crate::rng::Rng;
+use crate::collision::Hitbox;
+use crate::animation::AnimationState;
 
-#[derive(Clone, Copy)]
+#[derive(Clone)]
 pub struct Enemy {
     pub position: Position,
+    pub previous_position: Position, // 1ステップ前の位置（描画の補間用）
     pub width: f32,
     pub height:f32,
-    velocity: f32,
+    enemy_type: EnemyType,
+    life: u32,
+    velocity: f32, // 1秒あたりの移動量
+    sprite: &'static str,
+    score: u32,
+    hitbox: Hitbox,
+    emitter: Option<Emitter>, // 弾を撃たない敵は None
+    animation: AnimationState,
 }
 
 impl Enemy {
-    pub fn new(x: f32, y: f32) -> Enemy {
-        Enemy { position: Position::new(x, y), width: 216.0, height: 216.0, velocity: 2.0 }
+    pub fn new(enemy_type: EnemyType, x: f32, y: f32) -> Enemy {
+        let position = Position::new(x, y);
+        let stats = enemy_type.stats();
+        Enemy {
+            position,
+            previous_position: position,
+            width: stats.width,
+            height: stats.height,
+            enemy_type,
+            life: stats.life,
+            velocity: stats.velocity,
+            sprite: stats.sprite,
+            score: stats.score,
+            hitbox: stats.hitbox,
+            emitter: None,
+            animation: AnimationState::new("idle"),
+        }
     }
 
-    pub fn move_down(&mut self) {
-        self.position.y += self.velocity;
+    pub fn with_emitter(mut self, emitter: Emitter) -> Enemy {
+        self.emitter = Some(emitter);
+        self
+    }
+
+    // 移動速度に scale を掛ける（難易度の調整用）
+    pub fn with_speed_scale(mut self, scale: f32) -> Enemy {
+        self.velocity *= scale;
+        self
+    }
+
+    // 弾を撃つ敵なら、時間を進めて target に向けて撃った弾を out に加える
+    pub fn fire(&mut self, dt: f32, target: Position, rng: &mut Rng, out: &mut impl Extend<Bullet>) {
+        if let Some(emitter) = &mut self.emitter {
+            emitter.update(dt, self.position, target, rng, out);
+        }
+    }
+
+    pub fn move_down(&mut self, dt: f32) {
+        self.previous_position = self.position;
+        self.position.y += self.velocity * dt;
+        self.animation.update(dt);
+    }
+
+    pub fn animation(&self) -> &AnimationState {
+        &self.animation
+    }
+
+    // ダメージを受けてライフを減らし、倒されたら true を返す
+    pub fn take_damage(&mut self, damage: u32) -> bool {
+        self.life = self.life.saturating_sub(damage);
+        self.life == 0
     }
 
     pub fn get_position(&self) -> Position {
-        self.position.clone()
+        self.position
+    }
+
+    pub fn get_enemy_type(&self) -> EnemyType {
+        self.enemy_type
+    }
+
+    pub fn get_life(&self) -> u32 {
+        self.life
+    }
+
+    pub fn get_sprite(&self) -> &'static str {
+        self.sprite
+    }
+
+    pub fn get_hitbox(&self) -> &Hitbox {
+        &self.hitbox
+    }
+
+    pub fn get_score(&self) -> u32 {
+        self.score
+    }
+
+    // 前のステップと現在の位置を alpha で補間した描画用の位置
+    pub fn interpolated_position(&self, alpha: f32) -> Position {
+        self.previous_position.lerp(&self.position, alpha)
     }
 }
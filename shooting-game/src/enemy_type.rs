@@ -1,13 +1,57 @@
-#[derive(Clone, Copy)]
+use serde::Deserialize;
+use crate::collision::Hitbox;
+use crate::asset_manifest::default_hitbox;
+
+#[derive(Clone, Copy, PartialEq, Debug, Deserialize)]
 pub enum EnemyType {
     Regular,
     Fast,
     Strong,
 }
 
-#[derive(Clone, Copy)]
-pub struct EnemySpawnInfo {
-    pub enemy_type: EnemyType,
-    pub spawn_interval: f64,  // スポーン間隔（ミリ秒単位）
-    pub last_spawn_time: f64, // 最後にスポーンした時間
-}
\ No newline at end of file
+// 敵の種類ごとの性能
+pub struct EnemyStats {
+    pub life: u32,           // 倒すのに必要な命中数
+    pub velocity: f32,       // 1秒あたりの移動量
+    pub width: f32,
+    pub height: f32,
+    pub sprite: &'static str, // 描画に使う画像の名前
+    pub score: u32,          // 倒したときの得点
+    pub hitbox: Hitbox,      // 画像の時計の文字盤に合わせた当たり判定（assets.json の値を大きさに合わせたもの）
+}
+
+impl EnemyType {
+    pub fn stats(&self) -> EnemyStats {
+        match self {
+            EnemyType::Regular => EnemyStats {
+                life: 1,
+                velocity: 120.0,
+                width: 216.0,
+                height: 216.0,
+                sprite: "enemy",
+                score: 10,
+                hitbox: default_hitbox("enemy", 216.0, 216.0),
+            },
+            // 小さくて速いが、1発で倒せる
+            EnemyType::Fast => EnemyStats {
+                life: 1,
+                velocity: 360.0,
+                width: 144.0,
+                height: 144.0,
+                sprite: "enemy_fast",
+                score: 20,
+                hitbox: default_hitbox("enemy_fast", 144.0, 144.0),
+            },
+            // 大きくて遅いが、何発も当てないと倒せない
+            EnemyType::Strong => EnemyStats {
+                life: 5,
+                velocity: 60.0,
+                width: 288.0,
+                height: 288.0,
+                sprite: "enemy_strong",
+                score: 50,
+                hitbox: default_hitbox("enemy_strong", 288.0, 288.0),
+            },
+        }
+    }
+}
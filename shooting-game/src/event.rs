@@ -0,0 +1,40 @@
+use crate::enemy_type::EnemyType;
+use crate::game_state::GameState;
+use crate::position::Position;
+
+// 消えたエンティティの種類
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum EntityKind {
+    PlayerBullet,
+    EnemyBullet,
+    Enemy(EnemyType),
+}
+
+// エンティティが消えた理由
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum DespawnReason {
+    LeftPlayfield, // 画面の外に出た
+    Destroyed,     // 弾を当てられて倒された
+    Collided,      // 何かに当たって消えた
+}
+
+// Simulation の中で起きた出来事
+// フロントエンドや統計の集計は step のあとに drain_events で受け取る
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum GameEvent {
+    Despawned {
+        kind: EntityKind,
+        reason: DespawnReason,
+        position: Position,
+    },
+    // 敵が倒されずに画面の下から逃げた（penalty はスコアから引かれた点数）
+    EnemyEscaped { enemy_type: EnemyType, penalty: u32 },
+    // プレイヤーが当たってライフが減った（life は残りのライフ）
+    LifeLost { life: u32 },
+    // 新しいゲームを始めた
+    GameStarted { seed: u64 },
+    // ゲームオーバーからコンティニューした（penalty はスコアから引かれた点数）
+    Continued { penalty: u32 },
+    // 画面の状態が変わった
+    StateChanged { from: GameState, to: GameState },
+}
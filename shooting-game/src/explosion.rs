@@ -0,0 +1,33 @@
+use crate::animation::AnimationState;
+use crate::position::Position;
+
+// 爆発の画像とアニメーションの名前
+pub const EXPLOSION_SPRITE: &str = "explosion";
+pub const EXPLOSION_CLIP: &str = "explode";
+
+// 敵が倒されたところに出る爆発（当たり判定はない）
+#[derive(Clone)]
+pub struct Explosion {
+    pub position: Position,
+    pub size: f32, // 描画する大きさ（幅と高さ）
+    duration: f32, // 表示する時間（秒単位、アニメーションの長さ）
+    animation: AnimationState,
+}
+
+impl Explosion {
+    pub fn new(position: Position, size: f32, duration: f32) -> Explosion {
+        Explosion { position, size, duration, animation: AnimationState::new(EXPLOSION_CLIP) }
+    }
+
+    pub fn update(&mut self, dt: f32) {
+        self.animation.update(dt);
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.animation.time() >= self.duration
+    }
+
+    pub fn animation(&self) -> &AnimationState {
+        &self.animation
+    }
+}
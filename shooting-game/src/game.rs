@@ -1,231 +1,195 @@
-use wasm_bindgen::{
-    prelude::Closure, JsCast, JsValue,
-};
-use wasm_bindgen_futures;
-use anyhow::{anyhow, Result};
-use web_sys::{CanvasRenderingContext2d, HtmlCanvasElement, MouseEvent, window};
-use std::rc::Rc;
-use std::cell::RefCell;
-use crate::enemy::Enemy;
-use crate::player::Player;
-use crate::bullet::Bullet;
-use crate::renderer::{self, Renderer};
+use wasm_bindgen::JsValue;
+use web_sys::HtmlCanvasElement;
+use crate::renderer::Renderer;
+use crate::canvas_backend::CanvasBackend;
+use crate::render_backend::RenderBackend;
 use crate::logger::Logger;
 use crate::game_state::GameState;
-use crate::enemy_type::EnemyType;
-use crate::enemy_type::EnemySpawnInfo;
-
+use crate::input::Input;
+use crate::simulation::Simulation;
+use crate::stage::StageScript;
+use crate::timestep::FixedTimestep;
+use crate::statistics::Statistics;
+use crate::event::GameEvent;
+use crate::config::GameConfig;
+use crate::key_bindings::KeyBindings;
+use crate::callbacks::HostEvent;
+use crate::assets::Assets;
+use crate::asset_manifest::AssetManifest;
+use crate::sprite_atlas::{PackedAtlas, SpriteAtlas};
+use crate::explosion::{EXPLOSION_CLIP, EXPLOSION_SPRITE};
+use crate::key_bindings::Action;
+#[cfg(debug_assertions)]
+use crate::debug_overlay::{DebugOverlay, FrameSample};
+
+// canvas とキーボードイベントを Simulation につなぐアダプタ
 pub struct Game {
-    pub renderer: Renderer,
-    player: Player,
-    bullets: Vec<Bullet>,
-    enemies: Rc<RefCell<Vec<Enemy>>>,
-    score: u32,
-    state : GameState,
+    pub renderer: Renderer<CanvasBackend>,
+    simulation: Simulation,
     keys_pressed: Vec<String>,
-    last_enemy_spawn: f64,
-    enemies_spawn_info: Vec<EnemySpawnInfo>,
+    key_bindings: KeyBindings,
+    timestep: FixedTimestep,
+    last_timestamp: Option<f64>,
+    statistics: Statistics,
+    last_score: u32, // 前のフレームで知らせたスコア
+    #[cfg(debug_assertions)]
+    debug: DebugOverlay,
 }
 
 impl Game {
-    pub fn new() -> Result<Game, JsValue> {
-        Logger::log("Game new");
+    // atlas があればまとめた画像1枚を、なければ assets.json の画像を1枚ずつ読み込む
+    pub fn new(
+        canvas: HtmlCanvasElement,
+        seed: u64,
+        config: GameConfig,
+        key_bindings: KeyBindings,
+        atlas: Option<PackedAtlas>,
+        stage: StageScript,
+    ) -> Result<Game, JsValue> {
+        Logger::log(&format!("Game new (seed: {})", seed));
+
+        let (manifest, atlas) = match atlas {
+            Some(atlas) => atlas.into_parts(),
+            None => {
+                let manifest = AssetManifest::default();
+                let atlas = SpriteAtlas::from_manifest(&manifest);
+                (manifest, atlas)
+            }
+        };
+        // 爆発はこれから描くアニメーションが終わるまで出しておく
+        let explosion_duration = atlas.clip_duration(EXPLOSION_SPRITE, EXPLOSION_CLIP);
+        let assets = Assets::load(manifest);
+        let renderer = Renderer::new(CanvasBackend::new(canvas, assets)?, atlas);
+        let mut simulation = Simulation::with_stage(
+            renderer.backend().width() as f32,
+            renderer.backend().height() as f32,
+            seed,
+            stage,
+        );
+        simulation.set_config(config);
+        if let Some(duration) = explosion_duration {
+            simulation.set_explosion_duration(duration);
+        }
+        simulation.set_loading(true);
 
         Ok(Game {
-            renderer: Renderer::new(),
-            player: Player::new(400.0, 500.0),
-            bullets: Vec::new(),
-            enemies: Rc::new(RefCell::new(Vec::new())),
-            score: 0,
-            state: GameState::Playing,
+            renderer,
+            simulation,
             keys_pressed: Vec::new(),
-            last_enemy_spawn: 0.0,
-            enemies_spawn_info: vec![
-                EnemySpawnInfo {
-                    enemy_type: EnemyType::Regular,
-                    spawn_interval: 2000.0, // 2秒間隔
-                    last_spawn_time: 0.0,
-                }
-            ],
+            key_bindings,
+            timestep: FixedTimestep::default(),
+            last_timestamp: None,
+            statistics: Statistics::default(),
+            last_score: 0,
+            #[cfg(debug_assertions)]
+            debug: DebugOverlay::default(),
         })
     }
 
-    pub fn start(game_rc: Rc<RefCell<Self>>) {
-        let closure = Closure::wrap(Box::new(move |timestamp: f64| {
-            let mut game = game_rc.borrow_mut();
-            if game.state == GameState::Playing {
-                game.process_frame(timestamp);
+    // 読み込んだ画像とイベントリスナーはそのままで、新しいゲームをタイトル画面から始める
+    pub fn new_game(&mut self) {
+        let seed = random_seed();
+        Logger::log(&format!("Game new_game (seed: {})", seed));
+
+        self.simulation.reset(seed);
+        self.statistics = Statistics::default();
+        self.keys_pressed.clear();
+        self.timestep = FixedTimestep::default();
+        self.last_timestamp = None;
+        self.last_score = 0;
+    }
 
-                // 再度アニメーションフレームを要求
-                Game::start(game_rc.clone());
-            } else if game.state == GameState::GameOver {
-                game.renderer.draw_life(game.player.get_life());
-                game.game_over();
-            }
-        }) as Box<dyn FnMut(f64)>);
+    pub fn simulation(&self) -> &Simulation {
+        &self.simulation
+    }
 
-        web_sys::window()
-            .unwrap()
-            .request_animation_frame(closure.as_ref().unchecked_ref())
-            .unwrap();
+    pub fn simulation_mut(&mut self) -> &mut Simulation {
+        &mut self.simulation
+    }
 
-        closure.forget(); // クロージャをメモリに保持させる
+    pub fn key_bindings(&self) -> &KeyBindings {
+        &self.key_bindings
     }
 
-    fn process_frame(&mut self, current_time: f64) {
-        if self.last_enemy_spawn == 0.0 {
-            self.last_enemy_spawn = current_time;
-        }
+    // ホストページに知らせる出来事を out に加える
+    pub fn process_frame(&mut self, current_time: f64, out: &mut Vec<HostEvent>) {
+        // requestAnimationFrame のタイムスタンプはミリ秒単位
+        let elapsed = (current_time - self.last_timestamp.unwrap_or(current_time)) / 1000.0;
+        self.last_timestamp = Some(current_time);
 
-        let regular_enemy_info = self.enemies_spawn_info.get(0).unwrap();
-        if current_time - regular_enemy_info.last_spawn_time > regular_enemy_info.spawn_interval {
-            self.spawn_enemy(EnemyType::Regular);
-            if let Some(spawn_info) = self.enemies_spawn_info.get_mut(0) {
-                spawn_info.last_spawn_time = current_time;
-            }
+        if self.renderer.backend().assets().is_ready() {
+            self.simulation.set_loading(false);
         }
 
-        self.update();
-        self.render();
-    }
-
-    fn spawn_enemy(&mut self, enemy_type: EnemyType) {
-        let mut enemies = self.enemies.clone();
-        let canvas_width = self.renderer.canvas.width() as f64;
-        let state = self.state.clone();
-    
-        if state != GameState::Playing {
-            return;
+        let input = self.input();
+        let steps = self.timestep.advance(elapsed);
+        #[cfg(debug_assertions)]
+        let tick_start = now();
+        for _ in 0..steps {
+            self.simulation.step(&input, self.timestep.dt());
         }
-
-        let x = (js_sys::Math::random() * canvas_width) as f32;
-
-        match enemy_type {
-            EnemyType::Regular => {
-                enemies.borrow_mut().push(Enemy::new(x, 0.0));
-            }
-            EnemyType::Fast => {
-                //enemies.borrow_mut().push(Enemy::new(x, 0.0));
+        #[cfg(debug_assertions)]
+        self.debug.record(FrameSample { frame_time: elapsed * 1000.0, tick_time: now() - tick_start });
+
+        let score = self.simulation.score();
+        for event in self.simulation.drain_events() {
+            // 新しいゲームが始まったら記録を最初から取り直す（コンティニューでは続ける）
+            if let GameEvent::GameStarted { .. } = event {
+                self.statistics = Statistics::default();
             }
-            EnemyType::Strong => {
-                //enemies.borrow_mut().push(Enemy::new(x, 0.0));
-            }
-        }
-    }
+            self.statistics.record(&event);
 
-    fn update(&mut self) {
-        for bullet in &mut self.bullets {
-            bullet.move_up();
+            match event {
+                GameEvent::LifeLost { life } => out.push(HostEvent::LifeLost(life)),
+                GameEvent::StateChanged { to: GameState::GameOver, .. } => out.push(HostEvent::GameOver(score)),
+                GameEvent::StateChanged { to: GameState::StageClear, .. } => out.push(HostEvent::StageCleared(score)),
+                _ => {}
+            }
         }
 
-        for enemy in self.enemies.borrow_mut().iter_mut() {
-            enemy.move_down();
+        // スコアは加点、減点、やり直しのどれでも変わるので、フレームごとに前と比べる
+        if score != self.last_score {
+            self.last_score = score;
+            out.push(HostEvent::ScoreChanged(score));
         }
 
-        self.check_collisions();
-    }
-
-    // 衝突判定をチェックするメイン関数
-    fn check_collisions(&mut self) {
-        //Logger::log(&format!("life: {}", self.player.get_life()));
-        
-        // 弾と敵の衝突判定
-        self.check_bullet_enemy_collisions();
-
-        // プレイヤーと敵の衝突判定
-        self.check_player_enemy_collisions();
+        self.render(self.simulation.render_alpha(self.timestep.alpha()));
     }
 
-    // 弾と敵の衝突判定
-    fn check_bullet_enemy_collisions(&mut self) {
-        let collision_threshold = 150.0;
-
-        let mut i = 0;
-        let mut enems = self.enemies.borrow_mut();
-
-        while i < self.bullets.len() {
-            let bullet = &self.bullets[i];
-            let bullet_position = bullet.get_position();
-        
-            let mut j = 0;
-            
-            while j < enems.len() {
-                let enemy = &enems[j];
-                let enemy_position = enemy.get_position();
-        
-                let distance = ((bullet_position.x - enemy_position.x).powi(2)
-                    + (bullet_position.y - enemy_position.y).powi(2))
-                    .sqrt();
-        
-                if distance < collision_threshold {
-                    // 衝突した場合、弾と敵を削除しスコアを加算
-                    enems.remove(j);
-                    self.bullets.remove(i);
-                    self.score += 10;
-                    break; // 1つの弾が複数の敵に当たらないように
-                } else {
-                    j += 1;
-                }
-            }
-            if j == enems.len() {
-                i += 1;
+    pub fn key_down(&mut self, key: String) {
+        if !self.keys_pressed.contains(&key) {
+            // 押しっぱなしで切り替わり続けないよう、押した瞬間だけ切り替える
+            if self.key_bindings.action(&key) == Some(Action::Debug) {
+                self.toggle_debug();
             }
+            self.keys_pressed.push(key);
         }
     }
 
-    fn check_player_enemy_collisions(&mut self) {
-        let mut k = 0;
-        let mut enems = self.enemies.borrow_mut();
-    
-        while k < enems.len() {
-            let enemy = &enems[k];
-
-            let collision_threshold = 120.0;
-            //Logger::log(&format!("threshold: {}", collision_threshold));
-    
-            let enemy_position = enemy.get_position();
-            let player_position = self.player.get_position();
-    
-            let distance = ((player_position.x - enemy_position.x).powi(2)
-                + (player_position.y - enemy_position.y).powi(2))
-                .sqrt();
-
-            //Logger::log(&format!("distance: {}", distance));
-    
-            if distance < collision_threshold {
-                // 衝突した場合、プレイヤーのライフを減らし、敵を削除
-                self.player.decrease_life();
-                enems.remove(k);
-    
-                // プレイヤーのライフが0ならゲームオーバー
-                if self.player.get_life() == 0 {
-                    self.state = GameState::GameOver;
-                }
-            } else {
-                k += 1;
-            }
-        }
+    // デバッグ表示を切り替えて、表示しているかどうかを返す
+    // リリースビルドにはデバッグ表示がないので、いつも false を返す
+    pub fn toggle_debug(&mut self) -> bool {
+        let enabled = !self.debug_enabled();
+        self.set_debug(enabled);
+        self.debug_enabled()
     }
 
-    pub fn key_down(&mut self, key: String) {
-        if !self.keys_pressed.contains(&key) {
-            self.keys_pressed.push(key.clone());
-        }
+    #[cfg(debug_assertions)]
+    pub fn set_debug(&mut self, enabled: bool) {
+        self.debug.set_enabled(enabled);
+    }
 
-        if key == " " || key == "Space" {
-            // スペースバーが押された場合、弾丸を発射
-            self.fire_bullet();
-        }
+    #[cfg(not(debug_assertions))]
+    pub fn set_debug(&mut self, _enabled: bool) {}
 
-        if key == "ArrowRight" {
-            // 右矢印キーが押された場合、プレイヤーを右に移動
-            self.player.move_right(self.renderer.canvas.width() as f32);
-        } 
+    #[cfg(debug_assertions)]
+    pub fn debug_enabled(&self) -> bool {
+        self.debug.is_enabled()
+    }
 
-        if key == "ArrowLeft" {
-            // 左矢印キーが押された場合、プレイヤーを左に移動
-            self.player.move_left(self.renderer.canvas.width() as f32);
-        }
+    #[cfg(not(debug_assertions))]
+    pub fn debug_enabled(&self) -> bool {
+        false
     }
 
     pub fn key_up(&mut self, key: String) {
@@ -234,35 +198,36 @@ impl Game {
         }
     }
 
-    pub fn fire_bullet(&mut self) {
-        let bullet = Bullet::new(
-            self.player.position.x,
-            self.player.position.y
-        );
-        self.bullets.push(bullet);
+    // 押されているキーからシミュレーションへの入力を作る
+    fn input(&self) -> Input {
+        self.key_bindings.input(&self.keys_pressed)
     }
 
-    fn render(&self) {
-        self.renderer.clear();
-        self.renderer.draw_background();
-        self.renderer.draw_player(&self.player);
-        self.renderer.draw_bullets(&self.bullets);
-        self.renderer.draw_enemies(&self.enemies.borrow());
-        self.renderer.draw_score(self.score);
-        self.renderer.draw_life(self.player.get_life());
+    // alpha は前のステップから次のステップまでの補間係数
+    fn render(&mut self, alpha: f32) {
+        self.renderer.render(&self.simulation, &self.statistics, alpha);
 
-        // 中心座標の点を描画
-        self.renderer.draw_center_points(
-            &self.player,
-            &self.bullets,
-            &self.enemies.borrow(),
-        );
-    }
+        // 読み込み中は画像を使わない星空の上に進み具合を出す
+        if self.simulation.state() == GameState::Loading {
+            let assets = self.renderer.backend().assets();
+            let (progress, errors) = (assets.progress(), assets.errors());
+            self.renderer.draw_loading(progress, &errors);
+        }
 
-    pub fn game_over(&mut self) {
-        web_sys::window()
-            .unwrap()
-            .alert_with_message("Game Over!")
-            .unwrap();
+        #[cfg(debug_assertions)]
+        self.debug.draw(&mut self.renderer, &self.simulation);
     }
-}
\ No newline at end of file
+}
+
+// シードを指定しなかったときは開くたびに変える
+pub fn random_seed() -> u64 {
+    (js_sys::Math::random() * u32::MAX as f64) as u64
+}
+
+// 処理時間を測るための時刻（ミリ秒単位）
+#[cfg(debug_assertions)]
+fn now() -> f64 {
+    web_sys::window()
+        .and_then(|window| window.performance())
+        .map_or(0.0, |performance| performance.now())
+}
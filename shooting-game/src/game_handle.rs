@@ -0,0 +1,356 @@
+use std::cell::{Cell, RefCell};
+use std::rc::Rc;
+use wasm_bindgen::prelude::*;
+use wasm_bindgen::JsCast;
+use web_sys::{EventTarget, HtmlCanvasElement, KeyboardEvent};
+use crate::game::{random_seed, Game};
+use crate::callbacks::{Callbacks, HostEvent};
+use crate::config::{Difficulty, GameConfig, MAX_STARTING_LIVES};
+use crate::key_bindings::{Action, KeyBindings};
+use crate::logger::Logger;
+use crate::sprite_atlas::PackedAtlas;
+use crate::stage::StageScript;
+
+type KeyListener = Closure<dyn FnMut(KeyboardEvent)>;
+type FrameCallback = Closure<dyn FnMut(f64)>;
+
+// キーボードイベントを受け取る場所
+#[derive(Clone, Copy, PartialEq)]
+enum KeyboardTarget {
+    Window, // ページ全体（ゲームが1つだけのとき）
+    Canvas, // canvas にフォーカスがあるときだけ（同じページに複数のゲームを置くとき）
+}
+
+// JavaScript から渡される設定
+// {
+//   seed?: number,
+//   keyboardTarget?: "window" | "canvas",
+//   difficulty?: "easy" | "normal" | "hard",
+//   startingLives?: number,
+//   escapePenalty?: number,  // 敵を逃がすたびにスコアから引く点数（既定は0）
+//   keys?: { [action: string]: string | string[] },  // 例: { left: ["ArrowLeft", "a"], fire: "z" }
+//   atlas?: object,  // pack_atlas が書き出した atlas.json の中身
+//   stage?: object,  // ステージスクリプト（stages/stage1.json と同じ形、なければ組み込みのステージ）
+// }
+// keys に組み込みで他の操作にあるキーを書くと、そのキーはこちらに付け替える（keys の中で重なるとエラー）
+struct GameOptions {
+    seed: u64,
+    keyboard_target: KeyboardTarget,
+    config: GameConfig,
+    key_bindings: KeyBindings,
+    atlas: Option<PackedAtlas>,
+    stage: StageScript,
+}
+
+impl GameOptions {
+    fn from_js(options: &JsValue) -> Result<GameOptions, JsValue> {
+        let mut parsed = GameOptions {
+            seed: random_seed(),
+            keyboard_target: KeyboardTarget::Window,
+            config: GameConfig::default(),
+            key_bindings: KeyBindings::default(),
+            atlas: None,
+            stage: StageScript::default(),
+        };
+        if options.is_undefined() || options.is_null() {
+            return Ok(parsed);
+        }
+
+        let seed = js_sys::Reflect::get(options, &"seed".into())?;
+        if !seed.is_undefined() {
+            let seed = seed.as_f64().ok_or_else(|| JsError::new("options.seed must be a number"))?;
+            parsed.seed = seed as u64;
+        }
+
+        let target = js_sys::Reflect::get(options, &"keyboardTarget".into())?;
+        if !target.is_undefined() {
+            parsed.keyboard_target = match target.as_string().as_deref() {
+                Some("window") => KeyboardTarget::Window,
+                Some("canvas") => KeyboardTarget::Canvas,
+                _ => return Err(JsError::new("options.keyboardTarget must be \"window\" or \"canvas\"").into()),
+            };
+        }
+
+        let difficulty = js_sys::Reflect::get(options, &"difficulty".into())?;
+        if !difficulty.is_undefined() {
+            parsed.config.difficulty = difficulty
+                .as_string()
+                .and_then(|name| Difficulty::from_name(&name))
+                .ok_or_else(|| JsError::new("options.difficulty must be \"easy\", \"normal\" or \"hard\""))?;
+        }
+
+        let lives = js_sys::Reflect::get(options, &"startingLives".into())?;
+        if !lives.is_undefined() {
+            // 小数や大きすぎる数を u32 に丸めて黙って受け入れないようにする
+            parsed.config.starting_lives = lives
+                .as_f64()
+                .filter(|lives| lives.fract() == 0.0 && (1.0..=MAX_STARTING_LIVES as f64).contains(lives))
+                .ok_or_else(|| {
+                    JsError::new(&format!("options.startingLives must be an integer from 1 to {}", MAX_STARTING_LIVES))
+                })? as u32;
+        }
+
+        let penalty = js_sys::Reflect::get(options, &"escapePenalty".into())?;
+        if !penalty.is_undefined() {
+            parsed.config.escape_penalty = penalty
+                .as_f64()
+                .filter(|penalty| penalty.fract() == 0.0 && (0.0..=u32::MAX as f64).contains(penalty))
+                .ok_or_else(|| JsError::new("options.escapePenalty must be a non-negative integer"))?
+                as u32;
+        }
+
+        let keys = js_sys::Reflect::get(options, &"keys".into())?;
+        if !keys.is_undefined() {
+            let keys: js_sys::Object = keys.dyn_into().map_err(|_| JsError::new("options.keys must be an object"))?;
+            let mut assigned: Vec<(String, Action)> = Vec::new(); // options.keys で割り当てた操作
+            for action in js_sys::Object::keys(&keys).iter() {
+                let value = js_sys::Reflect::get(&keys, &action)?;
+                let action = action.as_string().unwrap_or_default();
+                let names: Vec<String> = if let Some(name) = value.as_string() {
+                    vec![name]
+                } else if js_sys::Array::is_array(&value) {
+                    js_sys::Array::from(&value).iter().filter_map(|name| name.as_string()).collect()
+                } else {
+                    return Err(JsError::new(&format!("options.keys.{} must be a string or an array of strings", action)).into());
+                };
+                let taken = parsed
+                    .key_bindings
+                    .set(&action, &names)
+                    .map_err(|err| JsError::new(&format!("options.keys: {}", err)))?;
+                // 同じキーを options.keys の2つの操作に割り当てたときはどちらを使うか決められない
+                // 組み込みの割り当てから取ったときは、その操作のキーが減ったことをログに残す
+                for (key, other) in taken {
+                    if let Some((name, _)) = assigned.iter().find(|(_, a)| *a == other) {
+                        return Err(JsError::new(&format!("options.keys: \"{}\" is assigned to both {} and {}", key, name, action)).into());
+                    }
+                    Logger::log(&format!("options.keys: \"{}\" is no longer bound to {:?}", key, other));
+                }
+                assigned.push((action.clone(), Action::from_name(&action).unwrap())); // set が通ったので名前は正しい
+            }
+        }
+
+        let atlas = js_sys::Reflect::get(options, &"atlas".into())?;
+        if !atlas.is_undefined() {
+            let json = js_sys::JSON::stringify(&atlas)?.as_string().unwrap_or_default();
+            parsed.atlas = Some(
+                PackedAtlas::from_json(&json).map_err(|err| JsError::new(&format!("options.atlas: {:#}", err)))?,
+            );
+        }
+
+        let stage = js_sys::Reflect::get(options, &"stage".into())?;
+        if !stage.is_undefined() {
+            let json = js_sys::JSON::stringify(&stage)?.as_string().unwrap_or_default();
+            parsed.stage = StageScript::from_json(&json).map_err(|err| JsError::new(&format!("options.stage: {:#}", err)))?;
+        }
+
+        Ok(parsed)
+    }
+}
+
+// JavaScript から作って持っておくゲーム1つ分
+// 同じページにいくつでも作れて、destroy（または free）でイベントリスナーとアニメーションを止める
+//
+//   const game = new GameHandle("gameCanvas", { difficulty: "hard" });
+//   game.on("gameOver", (score) => showMenu(score));
+//   game.destroy();
+#[wasm_bindgen]
+pub struct GameHandle {
+    game: Rc<RefCell<Game>>,
+    callbacks: Rc<RefCell<Callbacks>>,
+    keyboard_target: EventTarget,
+    key_down: Option<KeyListener>,
+    key_up: Option<KeyListener>,
+    frame: Rc<RefCell<Option<FrameCallback>>>, // 毎フレーム呼ぶクロージャ（自分自身を次のフレームに登録する）
+    frame_id: Rc<Cell<Option<i32>>>,           // 登録中の requestAnimationFrame の ID
+}
+
+#[wasm_bindgen]
+impl GameHandle {
+    // canvas は canvas 要素かその id
+    #[wasm_bindgen(constructor)]
+    pub fn new(canvas: JsValue, options: JsValue) -> Result<GameHandle, JsValue> {
+        let canvas = find_canvas(canvas)?;
+        let options = GameOptions::from_js(&options)?;
+
+        let keyboard_target: EventTarget = match options.keyboard_target {
+            KeyboardTarget::Window => window()?.into(),
+            KeyboardTarget::Canvas => {
+                // canvas がキーボードのフォーカスを受け取れるようにする
+                canvas.set_tab_index(0);
+                canvas.clone().into()
+            }
+        };
+
+        let game = Game::new(canvas, options.seed, options.config, options.key_bindings, options.atlas, options.stage)?;
+        let game = Rc::new(RefCell::new(game));
+
+        let mut handle = GameHandle {
+            game,
+            callbacks: Rc::new(RefCell::new(Callbacks::default())),
+            keyboard_target,
+            key_down: None,
+            key_up: None,
+            frame: Rc::new(RefCell::new(None)),
+            frame_id: Rc::new(Cell::new(None)),
+        };
+        handle.add_key_listeners()?;
+        handle.start_frames()?;
+        Ok(handle)
+    }
+
+    // 読み込んだ画像とイベントリスナーはそのままで、新しいゲームをタイトル画面から始める
+    #[wasm_bindgen(js_name = newGame)]
+    pub fn new_game(&self) {
+        self.game.borrow_mut().new_game();
+    }
+
+    pub fn pause(&self) {
+        self.game.borrow_mut().simulation_mut().pause();
+    }
+
+    pub fn resume(&self) {
+        self.game.borrow_mut().simulation_mut().resume();
+    }
+
+    pub fn score(&self) -> u32 {
+        self.game.borrow().simulation().score()
+    }
+
+    pub fn lives(&self) -> u32 {
+        self.game.borrow().simulation().player().get_life()
+    }
+
+    // 素材の読み込みの進み具合（0.0〜1.0）
+    #[wasm_bindgen(js_name = loadProgress)]
+    pub fn load_progress(&self) -> f64 {
+        self.game.borrow().renderer.backend().assets().progress().fraction()
+    }
+
+    // "loading" | "title" | "playing" | "paused" | "stageClear" | "gameOver" | "results"
+    pub fn state(&self) -> String {
+        self.game.borrow().simulation().state().name().to_string()
+    }
+
+    // デバッグ表示（当たり判定、グリッド、処理時間など）を切り替えて、表示しているかどうかを返す
+    // デバッグビルドでだけ表示される（キーボードの ` でも切り替えられる）
+    #[wasm_bindgen(js_name = toggleDebug)]
+    pub fn toggle_debug(&self) -> bool {
+        self.game.borrow_mut().toggle_debug()
+    }
+
+    #[wasm_bindgen(js_name = setDebug)]
+    pub fn set_debug(&self, enabled: bool) {
+        self.game.borrow_mut().set_debug(enabled);
+    }
+
+    // 出来事にコールバックを登録する（null を渡すと解除する）
+    // "scoreChanged"(score), "lifeLost"(lives), "gameOver"(score), "stageCleared"(score)
+    pub fn on(&self, event: &str, callback: Option<js_sys::Function>) -> Result<(), JsValue> {
+        self.callbacks.borrow_mut().set(event, callback)
+    }
+
+    // アニメーションを止めてイベントリスナーを取り除く（2回呼んでもよい）
+    pub fn destroy(&mut self) {
+        if let Some(id) = self.frame_id.take() {
+            if let Ok(window) = window() {
+                let _ = window.cancel_animation_frame(id);
+            }
+        }
+        // クロージャは自分自身の Rc を持っているので、ここで取り出して循環を切る
+        self.frame.borrow_mut().take();
+
+        for (event, listener) in [("keydown", self.key_down.take()), ("keyup", self.key_up.take())] {
+            if let Some(listener) = listener {
+                let _ = self
+                    .keyboard_target
+                    .remove_event_listener_with_callback(event, listener.as_ref().unchecked_ref());
+            }
+        }
+    }
+}
+
+impl GameHandle {
+    fn add_key_listeners(&mut self) -> Result<(), JsValue> {
+        let game = self.game.clone();
+        let key_down = Closure::wrap(Box::new(move |event: KeyboardEvent| {
+            let key = event.key();
+            let mut game = game.borrow_mut();
+            // 移動や発射に使うキーでページがスクロールしないようにする
+            if game.key_bindings().action(&key).is_some_and(|action| action.is_held()) {
+                event.prevent_default();
+            }
+            game.key_down(key);
+        }) as Box<dyn FnMut(KeyboardEvent)>);
+        self.keyboard_target
+            .add_event_listener_with_callback("keydown", key_down.as_ref().unchecked_ref())?;
+        self.key_down = Some(key_down);
+
+        let game = self.game.clone();
+        let key_up = Closure::wrap(Box::new(move |event: KeyboardEvent| {
+            game.borrow_mut().key_up(event.key());
+        }) as Box<dyn FnMut(KeyboardEvent)>);
+        self.keyboard_target
+            .add_event_listener_with_callback("keyup", key_up.as_ref().unchecked_ref())?;
+        self.key_up = Some(key_up);
+
+        Ok(())
+    }
+
+    // 毎フレーム同じクロージャを requestAnimationFrame に登録し直す
+    fn start_frames(&mut self) -> Result<(), JsValue> {
+        let game = self.game.clone();
+        let callbacks = self.callbacks.clone();
+        let frame = self.frame.clone();
+        let frame_id = self.frame_id.clone();
+        let mut events: Vec<HostEvent> = Vec::new();
+        let callback = Closure::wrap(Box::new(move |timestamp: f64| {
+            game.borrow_mut().process_frame(timestamp, &mut events);
+
+            // コールバックの中から GameHandle のメソッドを呼べるように、借用を返してから呼ぶ
+            let callbacks = callbacks.borrow().clone();
+            for event in events.drain(..) {
+                callbacks.dispatch(&event);
+            }
+
+            // destroy されたあとはクロージャがなくなっているので登録しない
+            if let Some(callback) = frame.borrow().as_ref() {
+                frame_id.set(request_frame(callback).ok());
+            }
+        }) as Box<dyn FnMut(f64)>);
+
+        self.frame_id.set(Some(request_frame(&callback)?));
+        *self.frame.borrow_mut() = Some(callback);
+        Ok(())
+    }
+}
+
+impl Drop for GameHandle {
+    fn drop(&mut self) {
+        self.destroy();
+    }
+}
+
+fn window() -> Result<web_sys::Window, JsValue> {
+    web_sys::window().ok_or_else(|| JsError::new("no global window exists").into())
+}
+
+fn request_frame(callback: &FrameCallback) -> Result<i32, JsValue> {
+    window()?.request_animation_frame(callback.as_ref().unchecked_ref())
+}
+
+// 要素そのものか id から canvas を探す
+fn find_canvas(canvas: JsValue) -> Result<HtmlCanvasElement, JsValue> {
+    if let Some(id) = canvas.as_string() {
+        let element = window()?
+            .document()
+            .and_then(|document| document.get_element_by_id(&id))
+            .ok_or_else(|| JsError::new(&format!("no element with id \"{}\"", id)))?;
+        return element
+            .dyn_into::<HtmlCanvasElement>()
+            .map_err(|_| JsError::new(&format!("element \"{}\" is not a canvas", id)).into());
+    }
+
+    canvas
+        .dyn_into::<HtmlCanvasElement>()
+        .map_err(|_| JsError::new("canvas must be a canvas element or its id").into())
+}
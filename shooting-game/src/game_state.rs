@@ -1,7 +1,38 @@
-#[derive(PartialEq, Clone)]
-
+// ゲームの画面の状態
+//
+// Loading --必須の素材が揃う--> Title
+// Title --Enter--> Playing --Esc--> Paused --Esc/Enter--> Playing
+// Playing --最後のウェーブを倒す--> StageClear --Enter/時間経過--> Results
+// Playing --ライフが0--> GameOver --Enter/時間経過--> Results
+// GameOver/Results --C--> Playing（スコアを減らしてコンティニュー）
+// Results --Enter--> Playing（最初からやり直す）
+#[derive(PartialEq, Clone, Copy, Debug)]
 pub enum GameState{
+    Loading,
+    Title,
     Playing,
+    Paused,
+    StageClear,
     GameOver,
-    Cleared,
-}
\ No newline at end of file
+    Results,
+}
+
+impl GameState {
+    // JavaScript に渡すときの名前
+    pub fn name(&self) -> &'static str {
+        match self {
+            GameState::Loading => "loading",
+            GameState::Title => "title",
+            GameState::Playing => "playing",
+            GameState::Paused => "paused",
+            GameState::StageClear => "stageClear",
+            GameState::GameOver => "gameOver",
+            GameState::Results => "results",
+        }
+    }
+
+    // プレイ画面（自機や敵）を描画する状態かどうか
+    pub fn shows_playfield(&self) -> bool {
+        matches!(self, GameState::Playing | GameState::Paused | GameState::StageClear | GameState::GameOver)
+    }
+}
@@ -0,0 +1,36 @@
+use crate::weapon::ShotPattern;
+
+// 1回の step に渡す入力
+// そのステップの時点で押されているキーの状態を表す
+#[derive(Clone, Copy, Default)]
+pub struct Input {
+    pub left: bool,
+    pub right: bool,
+    pub up: bool,
+    pub down: bool,
+    pub fire: bool,
+    pub weapon: Option<ShotPattern>, // 武器の切り替え
+    pub start: bool, // 開始・再開（Enter）
+    pub pause: bool, // 一時停止（Escape）
+    pub continue_game: bool, // ゲームオーバーからのコンティニュー（C）
+}
+
+impl Input {
+    pub fn new() -> Input {
+        Input::default()
+    }
+
+    pub fn clear(&mut self) {
+        *self = Input::default();
+    }
+
+    // 左右の入力を -1.0〜1.0 で返す（右が正）
+    pub fn direction_x(&self) -> f32 {
+        (self.right as i32 - self.left as i32) as f32
+    }
+
+    // 上下の入力を -1.0〜1.0 で返す（下が正）
+    pub fn direction_y(&self) -> f32 {
+        (self.down as i32 - self.up as i32) as f32
+    }
+}
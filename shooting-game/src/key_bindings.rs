@@ -0,0 +1,173 @@
+use anyhow::{anyhow, Result};
+use crate::input::Input;
+use crate::weapon::ShotPattern;
+
+// キーに割り当てられる操作
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Action {
+    Left,
+    Right,
+    Up,
+    Down,
+    Fire,
+    Start,
+    Pause,
+    Continue,
+    Weapon(ShotPattern),
+    Debug, // デバッグ表示の切り替え（Simulation への入力にはならない）
+}
+
+impl Action {
+    // 設定で使う名前から操作を探す
+    pub fn from_name(name: &str) -> Option<Action> {
+        match name {
+            "left" => Some(Action::Left),
+            "right" => Some(Action::Right),
+            "up" => Some(Action::Up),
+            "down" => Some(Action::Down),
+            "fire" => Some(Action::Fire),
+            "start" => Some(Action::Start),
+            "pause" => Some(Action::Pause),
+            "continue" => Some(Action::Continue),
+            "weapon1" => Some(Action::Weapon(ShotPattern::Single)),
+            "weapon2" => Some(Action::Weapon(ShotPattern::Twin)),
+            "weapon3" => Some(Action::Weapon(ShotPattern::Spread)),
+            "weapon4" => Some(Action::Weapon(ShotPattern::Laser)),
+            "debug" => Some(Action::Debug),
+            _ => None,
+        }
+    }
+
+    // 押している間ずっと効く操作かどうか（ページのスクロールなどを止める対象）
+    pub fn is_held(&self) -> bool {
+        matches!(self, Action::Left | Action::Right | Action::Up | Action::Down | Action::Fire)
+    }
+}
+
+// キーの名前（KeyboardEvent.key）と操作の対応
+#[derive(Clone, Debug)]
+pub struct KeyBindings {
+    bindings: Vec<(String, Action)>,
+}
+
+impl KeyBindings {
+    // action に割り当てるキーを keys で置き換える
+    // keys のうち他の操作に割り当てられていたキーはこちらに付け替え、外した割り当てを返す
+    pub fn set(&mut self, action: &str, keys: &[String]) -> Result<Vec<(String, Action)>> {
+        let action = Action::from_name(action).ok_or_else(|| anyhow!("unknown action \"{}\"", action))?;
+        let mut taken = Vec::new();
+        self.bindings.retain(|(key, a)| {
+            if *a == action {
+                return false;
+            }
+            if keys.contains(key) {
+                taken.push((key.clone(), *a));
+                return false;
+            }
+            true
+        });
+        self.bindings.extend(keys.iter().map(|key| (key.clone(), action)));
+        Ok(taken)
+    }
+
+    pub fn action(&self, key: &str) -> Option<Action> {
+        self.bindings.iter().find(|(k, _)| k == key).map(|(_, action)| *action)
+    }
+
+    // 押されているキーからシミュレーションへの入力を作る
+    pub fn input(&self, keys_pressed: &[String]) -> Input {
+        let mut input = Input::new();
+        for action in keys_pressed.iter().filter_map(|key| self.action(key)) {
+            match action {
+                Action::Left => input.left = true,
+                Action::Right => input.right = true,
+                Action::Up => input.up = true,
+                Action::Down => input.down = true,
+                Action::Fire => input.fire = true,
+                Action::Start => input.start = true,
+                Action::Pause => input.pause = true,
+                Action::Continue => input.continue_game = true,
+                // 複数押されていたら先に押したほうを使う
+                Action::Weapon(pattern) => {
+                    input.weapon.get_or_insert(pattern);
+                }
+                Action::Debug => {}
+            }
+        }
+        input
+    }
+}
+
+impl Default for KeyBindings {
+    fn default() -> KeyBindings {
+        let defaults = [
+            ("ArrowLeft", "left"),
+            ("ArrowRight", "right"),
+            ("ArrowUp", "up"),
+            ("ArrowDown", "down"),
+            (" ", "fire"),
+            ("Space", "fire"),
+            ("Enter", "start"),
+            ("Escape", "pause"),
+            ("c", "continue"),
+            ("C", "continue"),
+            ("1", "weapon1"),
+            ("2", "weapon2"),
+            ("3", "weapon3"),
+            ("4", "weapon4"),
+            ("`", "debug"),
+        ];
+        KeyBindings {
+            bindings: defaults
+                .iter()
+                .map(|(key, action)| (key.to_string(), Action::from_name(action).unwrap()))
+                .collect(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn keys(names: &[&str]) -> Vec<String> {
+        names.iter().map(|name| name.to_string()).collect()
+    }
+
+    #[test]
+    fn set_replaces_the_keys_of_an_action() {
+        let mut bindings = KeyBindings::default();
+        let taken = bindings.set("left", &keys(&["a"])).unwrap();
+        assert!(taken.is_empty());
+        assert_eq!(bindings.action("a"), Some(Action::Left));
+        assert_eq!(bindings.action("ArrowLeft"), None);
+    }
+
+    #[test]
+    fn set_takes_keys_from_other_actions() {
+        let mut bindings = KeyBindings::default();
+        let taken = bindings.set("fire", &keys(&["ArrowUp", "Enter"])).unwrap();
+        assert_eq!(taken, [("ArrowUp".to_string(), Action::Up), ("Enter".to_string(), Action::Start)]);
+        assert_eq!(bindings.action("ArrowUp"), Some(Action::Fire));
+        assert_eq!(bindings.action("Enter"), Some(Action::Fire));
+        assert_eq!(bindings.action(" "), None);
+    }
+
+    #[test]
+    fn set_rejects_unknown_actions() {
+        let mut bindings = KeyBindings::default();
+        assert!(bindings.set("jump", &keys(&["j"])).is_err());
+        assert_eq!(bindings.action("j"), None);
+    }
+
+    #[test]
+    fn input_combines_the_pressed_keys() {
+        let bindings = KeyBindings::default();
+        let input = bindings.input(&keys(&["ArrowLeft", "Space", "x", "3", "1", "`"]));
+        assert!(input.left && input.fire);
+        assert!(!input.right && !input.up && !input.down && !input.start && !input.pause);
+        // 武器は先に押したほうを使う
+        assert_eq!(input.weapon, Some(ShotPattern::Spread));
+        assert_eq!(bindings.input(&[]).weapon, None);
+    }
+}
@@ -1,22 +1,57 @@
-mod player;
-mod bullet;
-mod enemy;
-mod position;
-mod renderer;
+pub mod player;
+pub mod bullet;
+pub mod enemy;
+pub mod position;
+pub mod game_state;
+pub mod enemy_type;
+pub mod input;
+pub mod simulation;
+pub mod timestep;
+pub mod rng;
+pub mod stage;
+pub mod weapon;
+pub mod bullet_pattern;
+pub mod collision;
+pub mod spatial_grid;
+pub mod event;
+pub mod lifecycle;
+pub mod statistics;
+pub mod pool;
+pub mod config;
+pub mod key_bindings;
+pub mod asset_manifest;
+pub mod animation;
+pub mod sprite_atlas;
+pub mod explosion;
+pub mod particle;
+pub mod background;
+pub mod render_backend;
+pub mod renderer;
+pub mod software_backend;
+pub mod bitmap_font;
+#[cfg(debug_assertions)]
+pub mod debug_overlay;
+#[cfg(feature = "web")]
+mod canvas_backend;
+#[cfg(feature = "web")]
 mod game;
+#[cfg(feature = "web")]
 mod logger;
-mod game_state;
-mod engine;
-mod enemy_type;
+#[cfg(feature = "web")]
+mod assets;
+#[cfg(feature = "web")]
+mod callbacks;
+#[cfg(feature = "web")]
+mod game_handle;
+#[cfg(feature = "web")]
+pub use game_handle::GameHandle;
+#[cfg(feature = "web")]
 use wasm_bindgen::prelude::*;
-use crate::logger::Logger;
 
+// モジュールの読み込み時に呼ばれる
+// ゲームは JavaScript から GameHandle を作って始める
+#[cfg(feature = "web")]
 #[wasm_bindgen(start)]
-pub fn start() -> Result<(), JsValue> {
+pub fn start() {
     console_error_panic_hook::set_once();
-
-    // ゲームを初期化し、そのエラーハンドリングを行う
-    engine::start_game();
-
-    Ok(())
-}
\ No newline at end of file
+}
@@ -0,0 +1,31 @@
+use crate::position::Position;
+
+// 画面の外にこれだけはみ出したエンティティを消す
+pub const DESPAWN_MARGIN: f32 = 64.0;
+
+// プレイフィールドの範囲
+#[derive(Clone, Copy, Debug)]
+pub struct Playfield {
+    pub width: f32,
+    pub height: f32,
+    pub margin: f32,
+}
+
+impl Playfield {
+    // 大きさ width x height のエンティティが画面外（margin を含む）に完全に出ているか
+    pub fn is_outside(&self, position: Position, width: f32, height: f32) -> bool {
+        self.is_past_sides(position, width)
+            || position.y + height / 2.0 < -self.margin
+            || self.is_past_bottom(position, height)
+    }
+
+    // 左右どちらかの端から出ているか
+    pub fn is_past_sides(&self, position: Position, width: f32) -> bool {
+        position.x + width / 2.0 < -self.margin || position.x - width / 2.0 > self.width + self.margin
+    }
+
+    // 下端から出ているか
+    pub fn is_past_bottom(&self, position: Position, height: f32) -> bool {
+        position.y - height / 2.0 > self.height + self.margin
+    }
+}
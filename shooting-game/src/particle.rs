@@ -0,0 +1,271 @@
+use std::f32::consts::{FRAC_PI_2, PI};
+use crate::position::Position;
+use crate::rng::Rng;
+
+// 同時に存在できるパーティクルの上限
+// これを超える分は生まれないので、爆発が重なってもフレーム時間が延びすぎない
+pub const PARTICLE_BUDGET: usize = 1024;
+
+// パーティクルの出方と、生まれてから消えるまでの見た目の変化
+// 大きさ、色、不透明度は生まれたとき（start）から消えるとき（end）まで線形に変わる
+#[derive(Clone, Copy, Debug)]
+pub struct ParticleEffect {
+    pub rate: f32,            // 出し続けるときの1秒あたりの数
+    pub lifetime: (f32, f32), // 寿命の最小と最大（秒単位）
+    pub speed: (f32, f32),    // 速さの最小と最大（1秒あたりのピクセル数）
+    pub direction: f32,       // 飛ぶ向き（ラジアン、0 が右で下向きが正）
+    pub spread: f32,          // 向きのばらつき（direction から左右にこの角度まで）
+    pub gravity: f32,         // 下向きの加速度（1秒あたりのピクセル数）
+    pub start_size: f32,      // 半径（ピクセル単位）
+    pub end_size: f32,
+    pub start_color: [u8; 3],
+    pub end_color: [u8; 3],
+    pub start_alpha: f32,
+    pub end_alpha: f32,
+}
+
+// 敵を倒したときに飛び散る火花
+pub static ENEMY_EXPLOSION: ParticleEffect = ParticleEffect {
+    rate: 0.0,
+    lifetime: (0.3, 0.7),
+    speed: (80.0, 320.0),
+    direction: 0.0,
+    spread: PI,
+    gravity: 120.0,
+    start_size: 5.0,
+    end_size: 1.0,
+    start_color: [255, 240, 160],
+    end_color: [200, 40, 0],
+    start_alpha: 1.0,
+    end_alpha: 0.0,
+};
+
+// プレイヤーが被弾したときの光
+pub static PLAYER_HIT: ParticleEffect = ParticleEffect {
+    rate: 0.0,
+    lifetime: (0.15, 0.35),
+    speed: (150.0, 450.0),
+    direction: 0.0,
+    spread: PI,
+    gravity: 0.0,
+    start_size: 7.0,
+    end_size: 2.0,
+    start_color: [255, 255, 255],
+    end_color: [120, 180, 255],
+    start_alpha: 1.0,
+    end_alpha: 0.0,
+};
+
+// プレイヤーの機体の後ろから出る噴射
+pub static ENGINE_EXHAUST: ParticleEffect = ParticleEffect {
+    rate: 60.0,
+    lifetime: (0.15, 0.3),
+    speed: (150.0, 250.0),
+    direction: FRAC_PI_2,
+    spread: 0.2,
+    gravity: 0.0,
+    start_size: 4.0,
+    end_size: 1.0,
+    start_color: [150, 220, 255],
+    end_color: [40, 60, 255],
+    start_alpha: 0.8,
+    end_alpha: 0.0,
+};
+
+// 弾が敵に当たったところに出る小さな火花
+pub static BULLET_IMPACT: ParticleEffect = ParticleEffect {
+    rate: 0.0,
+    lifetime: (0.1, 0.25),
+    speed: (60.0, 200.0),
+    direction: FRAC_PI_2,
+    spread: 1.2,
+    gravity: 0.0,
+    start_size: 3.0,
+    end_size: 1.0,
+    start_color: [255, 255, 200],
+    end_color: [255, 140, 0],
+    start_alpha: 1.0,
+    end_alpha: 0.0,
+};
+
+#[derive(Clone, Debug)]
+pub struct Particle {
+    pub position: Position,
+    velocity_x: f32,
+    velocity_y: f32,
+    age: f32,
+    lifetime: f32,
+    effect: &'static ParticleEffect,
+}
+
+impl Particle {
+    // 寿命のうちどれだけ過ぎたか（0.0〜1.0）
+    pub fn life_fraction(&self) -> f32 {
+        (self.age / self.lifetime).min(1.0)
+    }
+
+    pub fn size(&self) -> f32 {
+        let t = self.life_fraction();
+        self.effect.start_size + (self.effect.end_size - self.effect.start_size) * t
+    }
+
+    pub fn alpha(&self) -> f32 {
+        let t = self.life_fraction();
+        self.effect.start_alpha + (self.effect.end_alpha - self.effect.start_alpha) * t
+    }
+
+    pub fn color(&self) -> [u8; 3] {
+        let t = self.life_fraction();
+        let (start, end) = (self.effect.start_color, self.effect.end_color);
+        std::array::from_fn(|i| (start[i] as f32 + (end[i] as f32 - start[i] as f32) * t) as u8)
+    }
+}
+
+// すべてのパーティクルをまとめて動かす
+// 見た目だけのものなので、ゲームの展開に影響しないよう乱数は Simulation のものとは別に持つ
+pub struct ParticleSystem {
+    particles: Vec<Particle>,
+    rng: Rng,
+}
+
+impl ParticleSystem {
+    pub fn new(seed: u64) -> ParticleSystem {
+        ParticleSystem {
+            particles: Vec::with_capacity(PARTICLE_BUDGET),
+            rng: Rng::new(seed),
+        }
+    }
+
+    // position から count 個を一度に出す（上限を超える分は出さない）
+    pub fn burst(&mut self, effect: &'static ParticleEffect, position: Position, count: usize) {
+        let count = count.min(PARTICLE_BUDGET - self.particles.len());
+        for _ in 0..count {
+            let angle = effect.direction + self.rng.range(-effect.spread, effect.spread);
+            let speed = self.rng.range(effect.speed.0, effect.speed.1);
+            self.particles.push(Particle {
+                position,
+                velocity_x: angle.cos() * speed,
+                velocity_y: angle.sin() * speed,
+                age: 0.0,
+                lifetime: self.rng.range(effect.lifetime.0, effect.lifetime.1),
+                effect,
+            });
+        }
+    }
+
+    pub fn update(&mut self, dt: f32) {
+        self.particles.retain_mut(|particle| {
+            particle.age += dt;
+            particle.velocity_y += particle.effect.gravity * dt;
+            particle.position.x += particle.velocity_x * dt;
+            particle.position.y += particle.velocity_y * dt;
+            particle.age < particle.lifetime
+        });
+    }
+
+    // すべて消して乱数を seed から作り直す（確保した領域はそのまま使い回す）
+    pub fn reset(&mut self, seed: u64) {
+        self.particles.clear();
+        self.rng = Rng::new(seed);
+    }
+
+    pub fn particles(&self) -> &[Particle] {
+        &self.particles
+    }
+
+    pub fn len(&self) -> usize {
+        self.particles.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.particles.is_empty()
+    }
+}
+
+// 決まった場所から出し続ける発生源（エンジンの噴射など）
+// 1ステップで出す数が1に満たない分は次のステップに持ち越す
+pub struct ParticleEmitter {
+    effect: &'static ParticleEffect,
+    pending: f32,
+}
+
+impl ParticleEmitter {
+    pub fn new(effect: &'static ParticleEffect) -> ParticleEmitter {
+        ParticleEmitter { effect, pending: 0.0 }
+    }
+
+    pub fn update(&mut self, dt: f32, position: Position, particles: &mut ParticleSystem) {
+        self.pending += self.effect.rate * dt;
+        let count = self.pending as usize;
+        self.pending -= count as f32;
+        particles.burst(self.effect, position, count);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ORIGIN: Position = Position { x: 100.0, y: 100.0 };
+
+    // 1秒に10個出し続け、消えないパーティクル
+    static STEADY: ParticleEffect = ParticleEffect { rate: 10.0, lifetime: (100.0, 100.0), ..ENEMY_EXPLOSION };
+    // 0.2〜0.4秒で消えるパーティクル
+    static SHORT_LIVED: ParticleEffect = ParticleEffect { lifetime: (0.2, 0.4), ..ENEMY_EXPLOSION };
+
+    #[test]
+    fn burst_stops_at_the_budget() {
+        let mut particles = ParticleSystem::new(1);
+        particles.burst(&ENEMY_EXPLOSION, ORIGIN, PARTICLE_BUDGET - 10);
+        particles.burst(&ENEMY_EXPLOSION, ORIGIN, 40);
+        assert_eq!(particles.len(), PARTICLE_BUDGET);
+        particles.burst(&ENEMY_EXPLOSION, ORIGIN, 1);
+        assert_eq!(particles.len(), PARTICLE_BUDGET);
+
+        // 消えた分だけまた出せる
+        particles.update(ENEMY_EXPLOSION.lifetime.1);
+        assert!(particles.is_empty());
+        particles.burst(&ENEMY_EXPLOSION, ORIGIN, 5);
+        assert_eq!(particles.len(), 5);
+    }
+
+    #[test]
+    fn emitter_carries_fractions_over_to_later_steps() {
+        let mut particles = ParticleSystem::new(1);
+        let mut emitter = ParticleEmitter::new(&STEADY);
+        // 1ステップに 1/6 個ずつなので、5ステップ目まではまだ出ない
+        for _ in 0..5 {
+            emitter.update(1.0 / 60.0, ORIGIN, &mut particles);
+        }
+        assert_eq!(particles.len(), 0);
+        for _ in 0..55 {
+            emitter.update(1.0 / 60.0, ORIGIN, &mut particles);
+        }
+        assert!((9..=10).contains(&particles.len()), "{}", particles.len());
+        for _ in 0..60 {
+            emitter.update(1.0 / 60.0, ORIGIN, &mut particles);
+        }
+        assert!((19..=20).contains(&particles.len()), "{}", particles.len());
+    }
+
+    #[test]
+    fn particles_fade_and_expire_with_their_lifetime() {
+        let mut particles = ParticleSystem::new(1);
+        particles.burst(&SHORT_LIVED, ORIGIN, 50);
+        let particle = &particles.particles()[0];
+        assert_eq!(particle.life_fraction(), 0.0);
+        assert_eq!(particle.size(), ENEMY_EXPLOSION.start_size);
+        assert_eq!(particle.alpha(), ENEMY_EXPLOSION.start_alpha);
+        assert_eq!(particle.color(), ENEMY_EXPLOSION.start_color);
+
+        particles.update(0.19);
+        assert_eq!(particles.len(), 50);
+        assert!(particles.particles().iter().all(|particle| particle.position != ORIGIN));
+        assert!(particles.particles().iter().all(|particle| particle.alpha() < ENEMY_EXPLOSION.start_alpha));
+
+        particles.update(0.1);
+        assert!(particles.len() < 50);
+        particles.update(0.15);
+        assert!(particles.is_empty());
+    }
+}
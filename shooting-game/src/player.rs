@@ -1,17 +1,53 @@
 use crate::position::Position;
+use crate::bullet::Bullet;
+use crate::weapon::{ShotPattern, Weapon};
+use crate::collision::Hitbox;
+use crate::asset_manifest::default_hitbox;
+use crate::animation::AnimationState;
+
+pub const START_LIFE: u32 = 3; // ゲーム開始時とコンティニュー時のライフ
+
+// 最高速度のこの割合より速く横に動いているときに体を傾ける
+const BANK_THRESHOLD: f32 = 0.3;
 
 pub struct Player {
     pub position: Position,
+    pub previous_position: Position, // 1ステップ前の位置（描画の補間用）
     pub width: f32,
     pub height: f32,
+    hitbox: Hitbox,      // 敵の体との当たり判定
+    core_hitbox: Hitbox, // 敵の弾との当たり判定（見た目よりずっと小さい）
     life: u32,
-    velocity: u32,
+    starting_life: u32, // コンティニューで戻すライフ
+    velocity_x: f32, // 現在の速度（1秒あたりの移動量）
+    velocity_y: f32,
+    speed: f32,        // 最高速度（1秒あたりの移動量）
+    acceleration: f32, // 1秒あたりの速度の変化量（0.0なら押した瞬間に最高速度になる）
+    weapon: Weapon,
+    animation: AnimationState,
 }
 
 impl Player {
     // コンストラクタ相当の関数
     pub fn new(x: f32, y: f32 ) -> Player {
-        Player { position: Position::new(x, y), width: 180.0, height: 180.0, life: 3, velocity: 30}
+        let position = Position::new(x, y);
+        let (width, height) = (180.0, 180.0);
+        Player {
+            position,
+            previous_position: position,
+            width,
+            height,
+            hitbox: default_hitbox("player", width, height),
+            core_hitbox: Hitbox::circle(12.0),
+            life: START_LIFE,
+            starting_life: START_LIFE,
+            velocity_x: 0.0,
+            velocity_y: 0.0,
+            speed: 420.0,
+            acceleration: 3000.0,
+            weapon: Weapon::new(ShotPattern::Single),
+            animation: AnimationState::new("idle"),
+        }
     }
 
     pub fn decrease_life(&mut self) {
@@ -20,27 +56,115 @@ impl Player {
         }
     }
 
+    pub fn with_life(mut self, life: u32) -> Player {
+        self.life = life;
+        self.starting_life = life;
+        self
+    }
+
+    // コンティニューしたときにライフを元に戻す
+    pub fn restore_life(&mut self) {
+        self.life = self.starting_life;
+    }
+
     // プレイヤーのライフを取得
     pub fn get_life(&self) -> u32 {
         self.life
     }
 
+    pub fn get_hitbox(&self) -> &Hitbox {
+        &self.hitbox
+    }
+
+    pub fn get_core_hitbox(&self) -> &Hitbox {
+        &self.core_hitbox
+    }
+
     // プレイヤーの現在の位置を返すメソッド
     pub fn get_position(&self) -> Position {
-        self.position.clone()
+        self.position
     }
 
-    // 左に移動するメソッド
-    pub fn move_left(&mut self, canvas_width: f32) {
-        if self.position.x > self.width / 2.0 {
-            self.position.x -= self.velocity as f32; // 左に移動
-        }
+    // position に移して止める（前の位置からの補間もしない）
+    pub fn move_to(&mut self, position: Position) {
+        self.position = position;
+        self.previous_position = position;
+        self.velocity_x = 0.0;
+        self.velocity_y = 0.0;
+    }
+
+    // ステップの最初に呼び、補間用に現在の位置を記録する
+    pub fn save_position(&mut self) {
+        self.previous_position = self.position;
+    }
+
+    // 前のステップと現在の位置を alpha で補間した描画用の位置
+    pub fn interpolated_position(&self, alpha: f32) -> Position {
+        self.previous_position.lerp(&self.position, alpha)
     }
 
-    // 右に移動するメソッド
-    pub fn move_right(&mut self, canvas_width: f32) {
-        if self.position.x < canvas_width - self.width / 2.0 {
-            self.position.x += self.velocity as f32; // 右に移動
+    pub fn animation(&self) -> &AnimationState {
+        &self.animation
+    }
+
+    pub fn weapon(&self) -> &Weapon {
+        &self.weapon
+    }
+
+    pub fn weapon_mut(&mut self) -> &mut Weapon {
+        &mut self.weapon
+    }
+
+    // 武器の時間を進め、firing が true なら撃てるときに弾を撃つ
+    pub fn fire(&mut self, dt: f32, firing: bool, out: &mut impl Extend<Bullet>) {
+        self.weapon.update(dt, firing, self.position, out);
+    }
+
+    // 入力方向（-1.0〜1.0）に向かって速度を変化させて移動し、画面内に収める
+    pub fn update(&mut self, direction_x: f32, direction_y: f32, dt: f32, width: f32, height: f32) {
+        // 斜め移動でも速くならないように方向を正規化する
+        let length = (direction_x * direction_x + direction_y * direction_y).sqrt();
+        let (direction_x, direction_y) = if length > 1.0 {
+            (direction_x / length, direction_y / length)
+        } else {
+            (direction_x, direction_y)
+        };
+
+        let target_x = direction_x * self.speed;
+        let target_y = direction_y * self.speed;
+        if self.acceleration > 0.0 {
+            let max_change = self.acceleration * dt;
+            self.velocity_x += (target_x - self.velocity_x).clamp(-max_change, max_change);
+            self.velocity_y += (target_y - self.velocity_y).clamp(-max_change, max_change);
+        } else {
+            self.velocity_x = target_x;
+            self.velocity_y = target_y;
+        }
+
+        self.position.x += self.velocity_x * dt;
+        self.position.y += self.velocity_y * dt;
+
+        // 画面の上下左右からはみ出さないようにする
+        let half_width = self.width / 2.0;
+        let half_height = self.height / 2.0;
+        if self.position.x < half_width || self.position.x > width - half_width {
+            self.position.x = self.position.x.clamp(half_width, width - half_width);
+            self.velocity_x = 0.0;
         }
+        if self.position.y < half_height || self.position.y > height - half_height {
+            self.position.y = self.position.y.clamp(half_height, height - half_height);
+            self.velocity_y = 0.0;
+        }
+
+        // 横に動いている向きに体を傾ける
+        let clip = if self.velocity_x < -self.speed * BANK_THRESHOLD {
+            "bank_left"
+        } else if self.velocity_x > self.speed * BANK_THRESHOLD {
+            "bank_right"
+        } else {
+            "idle"
+        };
+        self.animation.play(clip);
+        self.animation.update(dt);
     }
-}
\ No newline at end of file
+}
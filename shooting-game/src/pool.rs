@@ -0,0 +1,197 @@
+// 世代番号付きのオブジェクトプール
+// 消えた要素のスロットを使い回すので、生成と削除が O(1) で、毎フレームの確保も起きない
+
+// プールの要素を指すハンドル
+// スロットが使い回されると世代番号が変わるので、古いハンドルからは取り出せない
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Debug)]
+pub struct Handle {
+    index: u32,
+    generation: u32,
+}
+
+impl Handle {
+    pub fn index(&self) -> usize {
+        self.index as usize
+    }
+
+    pub fn generation(&self) -> u32 {
+        self.generation
+    }
+}
+
+struct Slot<T> {
+    generation: u32,
+    value: Option<T>,
+}
+
+pub struct Pool<T> {
+    slots: Vec<Slot<T>>,
+    free: Vec<u32>, // 空いているスロットの添字
+    len: usize,
+}
+
+impl<T> Pool<T> {
+    pub fn new() -> Pool<T> {
+        Pool::with_capacity(0)
+    }
+
+    pub fn with_capacity(capacity: usize) -> Pool<T> {
+        Pool {
+            slots: Vec::with_capacity(capacity),
+            free: Vec::with_capacity(capacity),
+            len: 0,
+        }
+    }
+
+    // 空いているスロットに value を入れてハンドルを返す
+    pub fn spawn(&mut self, value: T) -> Handle {
+        self.len += 1;
+
+        if let Some(index) = self.free.pop() {
+            let slot = &mut self.slots[index as usize];
+            slot.value = Some(value);
+            return Handle { index, generation: slot.generation };
+        }
+
+        let index = self.slots.len() as u32;
+        self.slots.push(Slot { generation: 0, value: Some(value) });
+        Handle { index, generation: 0 }
+    }
+
+    // handle の要素を取り除いて返す（すでに消えていれば None）
+    pub fn despawn(&mut self, handle: Handle) -> Option<T> {
+        let slot = self.slots.get_mut(handle.index())?;
+        if slot.generation != handle.generation {
+            return None;
+        }
+
+        let value = slot.value.take()?;
+        slot.generation = slot.generation.wrapping_add(1);
+        self.free.push(handle.index);
+        self.len -= 1;
+        Some(value)
+    }
+
+    pub fn get(&self, handle: Handle) -> Option<&T> {
+        self.slots
+            .get(handle.index())
+            .filter(|slot| slot.generation == handle.generation)
+            .and_then(|slot| slot.value.as_ref())
+    }
+
+    pub fn get_mut(&mut self, handle: Handle) -> Option<&mut T> {
+        self.slots
+            .get_mut(handle.index())
+            .filter(|slot| slot.generation == handle.generation)
+            .and_then(|slot| slot.value.as_mut())
+    }
+
+    pub fn contains(&self, handle: Handle) -> bool {
+        self.get(handle).is_some()
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    // 生きている要素をスロット順に返す
+    pub fn iter(&self) -> impl Iterator<Item = (Handle, &T)> {
+        self.slots.iter().enumerate().filter_map(|(index, slot)| {
+            slot.value.as_ref().map(|value| {
+                (Handle { index: index as u32, generation: slot.generation }, value)
+            })
+        })
+    }
+
+    pub fn iter_mut(&mut self) -> impl Iterator<Item = (Handle, &mut T)> {
+        self.slots.iter_mut().enumerate().filter_map(|(index, slot)| {
+            let generation = slot.generation;
+            slot.value.as_mut().map(|value| (Handle { index: index as u32, generation }, value))
+        })
+    }
+
+    pub fn values(&self) -> impl Iterator<Item = &T> {
+        self.slots.iter().filter_map(|slot| slot.value.as_ref())
+    }
+
+    pub fn values_mut(&mut self) -> impl Iterator<Item = &mut T> {
+        self.slots.iter_mut().filter_map(|slot| slot.value.as_mut())
+    }
+
+    // keep が false を返した要素を取り除く
+    pub fn retain(&mut self, mut keep: impl FnMut(Handle, &mut T) -> bool) {
+        for (index, slot) in self.slots.iter_mut().enumerate() {
+            let handle = Handle { index: index as u32, generation: slot.generation };
+            let remove = match slot.value.as_mut() {
+                Some(value) => !keep(handle, value),
+                None => false,
+            };
+            if remove {
+                slot.value = None;
+                slot.generation = slot.generation.wrapping_add(1);
+                self.free.push(index as u32);
+                self.len -= 1;
+            }
+        }
+    }
+
+    // すべての要素を取り除く（古いハンドルはすべて無効になる）
+    pub fn clear(&mut self) {
+        self.retain(|_, _| false);
+    }
+}
+
+impl<T> Default for Pool<T> {
+    fn default() -> Pool<T> {
+        Pool::new()
+    }
+}
+
+impl<T> Extend<T> for Pool<T> {
+    fn extend<I: IntoIterator<Item = T>>(&mut self, values: I) {
+        for value in values {
+            self.spawn(value);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn stale_handle_is_rejected_after_slot_is_reused() {
+        let mut pool = Pool::new();
+        let old = pool.spawn("old");
+        assert_eq!(pool.despawn(old), Some("old"));
+
+        let new = pool.spawn("new");
+        assert_eq!(new.index(), old.index());
+        assert_ne!(new.generation(), old.generation());
+        assert_eq!(pool.get(old), None);
+        assert!(pool.get_mut(old).is_none());
+        assert!(!pool.contains(old));
+        assert_eq!(pool.despawn(old), None);
+        assert_eq!(pool.get(new), Some(&"new"));
+        assert_eq!(pool.len(), 1);
+    }
+
+    #[test]
+    fn retain_and_clear_invalidate_handles() {
+        let mut pool = Pool::new();
+        let handles: Vec<Handle> = (0..4).map(|i| pool.spawn(i)).collect();
+        pool.retain(|_, value| *value % 2 == 0);
+        assert_eq!(pool.len(), 2);
+        assert_eq!(pool.get(handles[1]), None);
+        assert_eq!(pool.get(handles[2]), Some(&2));
+
+        pool.clear();
+        assert!(pool.is_empty());
+        assert!(handles.iter().all(|&handle| pool.get(handle).is_none()));
+        assert_eq!(pool.values().count(), 0);
+    }
+}
@@ -1,4 +1,4 @@
-#[derive(Clone, Copy)]
+#[derive(Clone, Copy, PartialEq, Debug)]
 pub struct Position{
     pub x: f32,
     pub y: f32,
@@ -8,4 +8,12 @@ impl Position{
     pub fn new(x: f32, y: f32) -> Position {
         Position{x, y}
     }
-}
\ No newline at end of file
+
+    // self から other へ t (0.0〜1.0) の割合で線形補間した位置を返す
+    pub fn lerp(&self, other: &Position, t: f32) -> Position {
+        Position::new(
+            self.x + (other.x - self.x) * t,
+            self.y + (other.y - self.y) * t,
+        )
+    }
+}
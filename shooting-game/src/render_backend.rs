@@ -0,0 +1,93 @@
+use crate::sprite_atlas::Rect;
+
+// 色（a は不透明度 0.0〜1.0）
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct Color {
+    pub r: u8,
+    pub g: u8,
+    pub b: u8,
+    pub a: f32,
+}
+
+impl Color {
+    pub const BLACK: Color = Color::rgb(0, 0, 0);
+    pub const WHITE: Color = Color::rgb(255, 255, 255);
+    pub const RED: Color = Color::rgb(255, 0, 0);
+
+    pub const fn rgb(r: u8, g: u8, b: u8) -> Color {
+        Color { r, g, b, a: 1.0 }
+    }
+
+    pub const fn rgba(r: u8, g: u8, b: u8, a: f32) -> Color {
+        Color { r, g, b, a }
+    }
+
+    pub const fn with_alpha(self, a: f32) -> Color {
+        Color { a, ..self }
+    }
+
+    // canvas の fillStyle などに渡す形
+    pub fn to_css(&self) -> String {
+        format!("rgba({}, {}, {}, {})", self.r, self.g, self.b, self.a)
+    }
+}
+
+// 塗りつぶすか、線で囲むか
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Paint {
+    Fill(Color),
+    Stroke(Color, f64), // 色と線の太さ
+}
+
+// 下に描かれている色との重ね方
+#[derive(Clone, Copy, PartialEq, Debug, Default)]
+pub enum BlendMode {
+    // 上に重ねる
+    #[default]
+    Normal,
+    // 下の色に足し合わせる（光の表現に使う）
+    Additive,
+}
+
+// 文字列の x 座標をどこに合わせるか
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum TextAlign {
+    Left,
+    Center,
+}
+
+// 描画先の長方形
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct Dest {
+    pub x: f64,
+    pub y: f64,
+    pub width: f64,
+    pub height: f64,
+}
+
+// 描画の最小限の命令
+// Renderer はこれだけを使って描くので、ブラウザの canvas でも画像ファイルへの書き出しでも同じ画面になる
+// 座標はすべて左上が原点のピクセル単位
+pub trait RenderBackend {
+    fn width(&self) -> f64;
+    fn height(&self) -> f64;
+
+    // 画面全体を color で塗りつぶす
+    fn clear(&mut self, color: Color);
+
+    // 画像 image の source の範囲を dest に引き伸ばして描く（flip_y なら上下を反転する）
+    // 画像がまだ読み込まれていなければ何もせず false を返す
+    fn draw_sprite(&mut self, image: &str, source: Rect, dest: Dest, flip_y: bool) -> bool;
+
+    fn draw_rect(&mut self, x: f64, y: f64, width: f64, height: f64, paint: Paint);
+
+    fn draw_circle(&mut self, x: f64, y: f64, radius: f64, paint: Paint);
+
+    fn draw_line(&mut self, from: (f64, f64), to: (f64, f64), color: Color, width: f64);
+
+    // y は文字のベースライン
+    fn draw_text(&mut self, text: &str, x: f64, y: f64, size: u32, color: Color, align: TextAlign);
+
+    // これ以降の描画の重ね方
+    fn set_blend_mode(&mut self, mode: BlendMode);
+}
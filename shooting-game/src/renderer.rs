@@ -1,207 +1,366 @@
-use std::sync::{Arc};
-use tokio::sync::Mutex;
-
-use anyhow::{anyhow, Result};
-use wasm_bindgen::{prelude::Closure, JsCast, JsValue};
-use wasm_bindgen_futures::{spawn_local, JsFuture};
 use crate::enemy::Enemy;
 use crate::player::Player;
 use crate::bullet::Bullet;
-use crate::logger::Logger;
-use web_sys::{console, window, CanvasRenderingContext2d, Document, HtmlCanvasElement, HtmlImageElement};
-
-#[derive(Clone)]
-pub struct Renderer {
-    pub ctx: CanvasRenderingContext2d,
-    pub canvas: HtmlCanvasElement,
-    pub images: std::collections::HashMap<String, web_sys::HtmlImageElement>,
+use crate::pool::Pool;
+use crate::asset_manifest::LoadProgress;
+use crate::explosion::{Explosion, EXPLOSION_SPRITE};
+use crate::particle::ParticleSystem;
+use crate::background::{Background, LayerKind, Star};
+use crate::animation::AnimationState;
+use crate::sprite_atlas::SpriteAtlas;
+use crate::render_backend::{BlendMode, Color, Dest, Paint, RenderBackend, TextAlign};
+use crate::simulation::{Simulation, CONTINUE_PENALTY_RATE};
+use crate::statistics::Statistics;
+use crate::game_state::GameState;
+#[cfg(debug_assertions)]
+use crate::collision::{Hitbox, Shape};
+#[cfg(debug_assertions)]
+use crate::pool::Handle;
+#[cfg(debug_assertions)]
+use crate::position::Position;
+#[cfg(debug_assertions)]
+use crate::spatial_grid::SpatialGrid;
+
+// ゲーム画面の描き方
+// 描画先（RenderBackend）を差し替えれば、canvas にも画像ファイルにも同じ画面を描ける
+pub struct Renderer<B: RenderBackend> {
+    backend: B,
+    atlas: SpriteAtlas, // スプライト名から画像とコマの位置を引く表
 }
 
-impl Renderer {
-    pub fn new() -> Renderer {
-        let window = window().expect("no global `window` exists");
-        let document = window.document().unwrap();
-
-        let canvas: HtmlCanvasElement = document
-            .get_element_by_id("gameCanvas")
-            .unwrap()
-            .dyn_into::<HtmlCanvasElement>()
-            .expect("gameCanvas should be a HtmlCanvasElement");
-
-        let ctx = canvas
-            .get_context("2d")
-            .expect("should have 2d context")
-            .unwrap()
-            .dyn_into::<CanvasRenderingContext2d>()
-            .expect("gameCanvas should be a HtmlCanvasElement");
-
-        Renderer {
-            ctx,
-            canvas,
-            images: std::collections::HashMap::new(),
-        }
-    }
-
-    pub fn load_images(&mut self) {
-        let image_sources = vec![
-            ("player", "images/player.png"),
-            ("bullet", "images/bullet.png"),
-            ("enemy", "images/enemy.png"),
-            ("heart", "images/heart.png"),
-            ("background", "images/background.png"),
-        ];
-
-        // Arc<Mutex<Renderer>> を作成
-        let renderer_arc = Arc::new(Mutex::new(self.clone()));
-
-        for (name, src) in image_sources {
-            let renderer_clone = Arc::clone(&renderer_arc); // renderer の clone を作成
-
-            spawn_local(async move {
-                match load_image(name, src).await {
-                    Ok(image) => {
-                        // 画像のロード成功後に Renderer の images に挿入
-                        let mut renderer = renderer_clone.lock().await;  // ここで lock() を await
-                        renderer.images.insert(name.to_string(), image);
-                        console::log_1(&format!("Image '{}' loaded successfully!", name).into());
-                        // 画像を使う処理をここで行う
-                    }
-                    Err(e) => {
-                        // 画像ロードに失敗した場合の処理
-                        console::log_1(&format!("Error loading image: {:?}", e).into());
-                    }
-                }
-            });
-        }
+impl<B: RenderBackend> Renderer<B> {
+    pub fn new(backend: B, atlas: SpriteAtlas) -> Renderer<B> {
+        Renderer { backend, atlas }
     }
 
-    pub fn clear(&self) {
-        self.ctx.clear_rect(0.0, 0.0, self.canvas.width() as f64, self.canvas.height() as f64);
+    pub fn backend(&self) -> &B {
+        &self.backend
     }
 
-    pub fn draw_background(&self) {
-        if let Some(background) = self.images.get("background") {
-            self.ctx.draw_image_with_html_image_element(
-                background,
-                0.0,
-                0.0,
-            ).unwrap();
+    pub fn backend_mut(&mut self) -> &mut B {
+        &mut self.backend
+    }
+
+    // 今の状態の画面を1枚描く（読み込み中は背景だけ）
+    // alpha は前のステップから次のステップまでの補間係数
+    pub fn render(&mut self, simulation: &Simulation, statistics: &Statistics, alpha: f32) {
+        let state = simulation.state();
+
+        self.clear();
+        self.draw_background(simulation.background(), alpha);
+        if state == GameState::Loading {
+            return;
+        }
+        if state.shows_playfield() {
+            self.render_playfield(simulation, alpha);
+        }
+
+        let y = self.backend.height() / 2.0;
+        match state {
+            GameState::Loading => {}
+            GameState::Title => {
+                self.draw_overlay(0.4);
+                self.draw_text_centered("SHOOTING GAME", y - 60.0, 56);
+                self.draw_text_centered("Press Enter to start", y + 10.0, 24);
+                self.draw_text_centered("Arrow keys: move   Space: fire   1-4: weapon   Esc: pause", y + 60.0, 18);
+            }
+            GameState::Playing => {}
+            GameState::Paused => {
+                self.draw_overlay(0.5);
+                self.draw_text_centered("PAUSED", y, 48);
+                self.draw_text_centered("Press Esc or Enter to resume", y + 50.0, 20);
+            }
+            GameState::StageClear => {
+                self.draw_overlay(0.3);
+                self.draw_text_centered("STAGE CLEAR", y, 48);
+            }
+            GameState::GameOver => {
+                self.draw_overlay(0.5);
+                self.draw_text_centered("GAME OVER", y, 48);
+                self.draw_continue_hint(simulation, y + 50.0);
+            }
+            GameState::Results => {
+                self.draw_overlay(0.6);
+                self.draw_text_centered("RESULTS", y - 120.0, 48);
+                self.draw_text_centered(&format!("Score: {}", simulation.score()), y - 50.0, 28);
+                self.draw_text_centered(&format!("Destroyed: {}", statistics.enemies_destroyed), y - 10.0, 20);
+                self.draw_text_centered(&format!("Escaped: {}", statistics.enemies_escaped), y + 20.0, 20);
+                self.draw_text_centered(&format!("Accuracy: {:.0}%", statistics.accuracy() * 100.0), y + 50.0, 20);
+                self.draw_text_centered("Press Enter to play again", y + 110.0, 24);
+                self.draw_continue_hint(simulation, y + 150.0);
+            }
         }
     }
 
-    pub fn draw_player(&self, player: &Player) {
-        if let Some(player_image) = self.images.get("player") {
-            self.ctx.draw_image_with_html_image_element(
-                player_image,
-                (player.position.x - player.width / 2.0) as f64,
-                (player.position.y - player.height / 2.0) as f64,
-            ).unwrap();
+    fn render_playfield(&mut self, simulation: &Simulation, alpha: f32) {
+        self.draw_player(simulation.player(), alpha);
+        self.draw_bullets(simulation.bullets(), alpha);
+        self.draw_enemies(simulation.enemies(), alpha);
+        self.draw_explosions(simulation.explosions());
+        self.draw_particles(simulation.particles());
+        self.draw_bullets(simulation.enemy_bullets(), alpha);
+        self.draw_score(simulation.score());
+        self.draw_life(simulation.player().get_life());
+    }
+
+    fn draw_continue_hint(&mut self, simulation: &Simulation, y: f64) {
+        if simulation.can_continue() {
+            let penalty = (CONTINUE_PENALTY_RATE * 100.0).round();
+            self.draw_text_centered(&format!("Press C to continue (score -{}%)", penalty), y, 20);
         }
     }
 
-    pub fn draw_bullets(&self, bullets: &Vec<Bullet>) {
-        if let Some(bullet_image) = self.images.get("bullet") {
-            for bullet in bullets {
-                self.ctx.draw_image_with_html_image_element(
-                    bullet_image,
-                    (bullet.position.x - bullet.width / 2.0) as f64,
-                    (bullet.position.y - bullet.height / 2.0) as f64,
-                ).unwrap();
+    pub fn clear(&mut self) {
+        self.backend.clear(Color::BLACK);
+    }
+
+    // 背景の層を奥から順に描く
+    pub fn draw_background(&mut self, background: &Background, alpha: f32) {
+        for layer in background.layers() {
+            let distance = layer.interpolated_distance(alpha);
+            match &layer.layer.kind {
+                LayerKind::Image { sprite, flip } => self.draw_image_layer(sprite, distance, *flip),
+                LayerKind::Starfield { .. } => self.draw_starfield(&layer.stars, distance),
             }
         }
     }
 
-    pub fn draw_enemies(&self, enemies: &Vec<Enemy>) {
-        if let Some(enemy_image) = self.images.get("enemy") {
-            for enemy in enemies {
-                self.ctx.draw_image_with_html_image_element(
-                    enemy_image,
-                    (enemy.position.x - enemy.width / 2.0) as f64,
-                    (enemy.position.y - enemy.height / 2.0) as f64
-                ).unwrap();
+    // 画像を画面の幅に合わせて縦に敷き詰める
+    // flip が true なら1枚おきに上下を反転させる（上下の端がつながっていない画像の継ぎ目を隠す）
+    fn draw_image_layer(&mut self, name: &str, distance: f64, flip: bool) {
+        let Some(sprite) = self.atlas.sprite(name) else {
+            return;
+        };
+
+        let Some(frame) = sprite.frame(None) else {
+            return;
+        };
+        let width = self.backend.width();
+        let tile_height = frame.height as f64 * width / frame.width as f64;
+        let wraps = (distance / tile_height).floor() as i64;
+        let mut y = distance.rem_euclid(tile_height) - tile_height;
+        let mut index = 0;
+        while y < self.backend.height() {
+            // 継ぎ目に隙間ができないよう1ピクセル重ねる
+            let dest = Dest { x: 0.0, y, width, height: tile_height + 1.0 };
+            // 読み込みが終わるまでは描かない（星空の層だけが見える）
+            let flip_y = flip && (index + wraps).rem_euclid(2) == 1;
+            if !self.backend.draw_sprite(&sprite.image, frame, dest, flip_y) {
+                return;
             }
+            y += tile_height;
+            index += 1;
         }
     }
 
-    pub fn draw_score(&self, score: u32) {
-        self.ctx.set_font("20px Arial");
-        self.ctx.set_fill_style(&wasm_bindgen::JsValue::from_str("white"));
-        self.ctx.fill_text(&format!("Score: {}", score), 20.0, self.canvas.height() as f64 - 20.0).unwrap();
+    fn draw_starfield(&mut self, stars: &[Star], distance: f64) {
+        let height = self.backend.height();
+        for star in stars {
+            let y = (star.y as f64 + distance).rem_euclid(height);
+            let size = star.size as f64;
+            let color = Color::WHITE.with_alpha(star.brightness);
+            self.backend.draw_rect(star.x as f64 - size / 2.0, y - size / 2.0, size, size, Paint::Fill(color));
+        }
     }
 
-    pub fn draw_life(&self, life: u32) {
-        let heart_size = 45.0;
-        if let Some(heart_image) = self.images.get("heart") {
-            for i in 0..life {
-                self.ctx.draw_image_with_html_image_element(
-                    heart_image,
-                    self.canvas.width() as f64 - 10.0 - (i + 1) as f64 * (heart_size + 5.0),
-                    self.canvas.height() as f64 - heart_size - 10.0
-                ).unwrap();
+    pub fn draw_player(&mut self, player: &Player, alpha: f32) {
+        let position = player.interpolated_position(alpha);
+        self.draw_sprite(
+            "player",
+            Some(player.animation()),
+            position.x as f64,
+            position.y as f64,
+            Some((player.width as f64, player.height as f64)),
+        );
+    }
+
+    pub fn draw_bullets(&mut self, bullets: &Pool<Bullet>, alpha: f32) {
+        for bullet in bullets.values() {
+            let position = bullet.interpolated_position(alpha);
+            self.draw_sprite(
+                bullet.get_sprite(),
+                None,
+                position.x as f64,
+                position.y as f64,
+                Some((bullet.width as f64, bullet.height as f64)),
+            );
+        }
+    }
+
+    pub fn draw_enemies(&mut self, enemies: &Pool<Enemy>, alpha: f32) {
+        for enemy in enemies.values() {
+            let position = enemy.interpolated_position(alpha);
+            let size = Some((enemy.width as f64, enemy.height as f64));
+            // 種類ごとの画像がまだ読み込まれていなければ通常の敵の画像で代用する
+            if !self.draw_sprite(enemy.get_sprite(), Some(enemy.animation()), position.x as f64, position.y as f64, size) {
+                self.draw_sprite("enemy", Some(enemy.animation()), position.x as f64, position.y as f64, size);
             }
         }
     }
 
-    pub fn draw_center_points(&self, player: &Player, bullets: &Vec<Bullet>, enemies: &Vec<Enemy>) {
-        // プレイヤーの中心座標
-        let player_center_x = player.position.x;
-        let player_center_y = player.position.y ;
-        self.draw_point(player_center_x as f64, player_center_y as f64, "red");
+    pub fn draw_explosions(&mut self, explosions: &Pool<Explosion>) {
+        for explosion in explosions.values() {
+            let size = explosion.size as f64;
+            self.draw_sprite(
+                EXPLOSION_SPRITE,
+                Some(explosion.animation()),
+                explosion.position.x as f64,
+                explosion.position.y as f64,
+                Some((size, size)),
+            );
+        }
+    }
+
+    // パーティクルは光って見えるように、下の色に足し合わせて描く
+    pub fn draw_particles(&mut self, particles: &ParticleSystem) {
+        self.backend.set_blend_mode(BlendMode::Additive);
+        for particle in particles.particles() {
+            let [r, g, b] = particle.color();
+            self.backend.draw_circle(
+                particle.position.x as f64,
+                particle.position.y as f64,
+                particle.size() as f64,
+                Paint::Fill(Color::rgba(r, g, b, particle.alpha())),
+            );
+        }
+        self.backend.set_blend_mode(BlendMode::Normal);
+    }
+
+    // スプライトの今のコマを、アンカーが (x, y) に来るように描画する
+    // size がなければコマの元の大きさで描く。画像がまだ読み込まれていなければ false を返す
+    fn draw_sprite(&mut self, name: &str, animation: Option<&AnimationState>, x: f64, y: f64, size: Option<(f64, f64)>) -> bool {
+        let Some(sprite) = self.atlas.sprite(name) else {
+            return false;
+        };
+
+        let Some(frame) = sprite.frame(animation) else {
+            return false;
+        };
+        let (width, height) = size.unwrap_or((frame.width as f64, frame.height as f64));
+        let dest = Dest {
+            x: x - sprite.anchor[0] as f64 * width,
+            y: y - sprite.anchor[1] as f64 * height,
+            width,
+            height,
+        };
+        self.backend.draw_sprite(&sprite.image, frame, dest, false)
+    }
+
+    pub fn draw_score(&mut self, score: u32) {
+        let y = self.backend.height() - 20.0;
+        self.backend.draw_text(&format!("Score: {}", score), 20.0, y, 20, Color::WHITE, TextAlign::Left);
+    }
 
-        // 弾丸の中心座標
-        for bullet in bullets {
-            let bullet_center_x = bullet.position.x;
-            let bullet_center_y = bullet.position.y;
-            self.draw_point(bullet_center_x as f64, bullet_center_y as f64, "blue");
+    pub fn draw_life(&mut self, life: u32) {
+        let heart_size = 45.0;
+        let (width, height) = (self.backend.width(), self.backend.height());
+        for i in 0..life {
+            self.draw_sprite(
+                "heart",
+                None,
+                width - 10.0 - (i + 1) as f64 * (heart_size + 5.0),
+                height - heart_size - 10.0,
+                None,
+            );
         }
+    }
+
+    // 読み込みの進み具合のバーと、読み込めなかった素材のエラーを描画する
+    pub fn draw_loading(&mut self, progress: LoadProgress, errors: &[String]) {
+        let width = self.backend.width();
+        let height = self.backend.height();
+        let bar_width = width * 0.6;
+        let bar_x = (width - bar_width) / 2.0;
+        let bar_y = height / 2.0;
 
-        // 敵の中心座標
-        for enemy in enemies {
-            let enemy_center_x = enemy.position.x;
-            let enemy_center_y = enemy.position.y;
-            self.draw_point(enemy_center_x as f64, enemy_center_y as f64, "green");
+        self.draw_overlay(0.5);
+        self.draw_text_centered(&format!("Loading... {}/{}", progress.loaded, progress.total), bar_y - 20.0, 24);
+        self.backend.draw_rect(bar_x, bar_y, bar_width, 16.0, Paint::Stroke(Color::WHITE, 1.0));
+        self.backend.draw_rect(bar_x, bar_y, bar_width * progress.fraction(), 16.0, Paint::Fill(Color::WHITE));
+
+        for (i, error) in errors.iter().enumerate() {
+            self.backend.draw_text(error, 20.0, bar_y + 60.0 + i as f64 * 22.0, 16, Color::RED, TextAlign::Left);
         }
     }
 
-    pub fn draw_point(&self, x: f64, y: f64, color: &str) {
-        self.ctx.begin_path();
-        self.ctx.arc(x, y, 3.0, 0.0, std::f64::consts::PI * 2.0).unwrap(); // 半径3の円を描画
-        self.ctx.set_fill_style(&wasm_bindgen::JsValue::from_str(color));
-        self.ctx.fill();
-        self.ctx.close_path();
+    // 画面全体を半透明の黒で覆う（一時停止やゲームオーバーの表示の下地）
+    pub fn draw_overlay(&mut self, opacity: f32) {
+        let (width, height) = (self.backend.width(), self.backend.height());
+        self.backend.draw_rect(0.0, 0.0, width, height, Paint::Fill(Color::BLACK.with_alpha(opacity)));
     }
-}
 
-async fn load_image(name: &str, src: &str) -> Result<(HtmlImageElement), JsValue>{
-    let image = web_sys::HtmlImageElement::new().unwrap();
-    wait_for_image_to_load(&image).await?;
-    image.set_src(src);
-    Ok((image))
+    // 画面の横方向の中央に文字を描画する
+    pub fn draw_text_centered(&mut self, text: &str, y: f64, size: u32) {
+        let x = self.backend.width() / 2.0;
+        self.backend.draw_text(text, x, y, size, Color::WHITE, TextAlign::Center);
+    }
 }
 
-async fn wait_for_image_to_load(image: &HtmlImageElement) -> Result<(), JsValue> {
-    // JavaScript の Promise を作成
-    let promise = js_sys::Promise::new(&mut |resolve, reject| {
-        // onload イベントの設定
-        let resolve = resolve.clone();
-        let closure = Closure::wrap(Box::new(move || {
-            resolve.call0(&JsValue::NULL).unwrap();
-        }) as Box<dyn FnMut()>);
-
-        image.set_onload(Some(closure.as_ref().unchecked_ref()));
-        closure.forget();
-
-        // onerror イベントの設定（エラーハンドリング）
-        let reject = reject.clone();
-        let error_closure = Closure::wrap(Box::new(move || {
-            reject.call0(&JsValue::NULL).unwrap();
-        }) as Box<dyn FnMut()>);
-
-        image.set_onerror(Some(error_closure.as_ref().unchecked_ref()));
-        error_closure.forget();
-    });
-
-    // Promise が解決するのを待つ
-    JsFuture::from(promise).await?;
-    Ok(())
-}
\ No newline at end of file
+// デバッグ表示（debug_overlay.rs）から使う描画
+#[cfg(debug_assertions)]
+impl<B: RenderBackend> Renderer<B> {
+    pub fn draw_debug_hitbox(&mut self, position: Position, hitbox: &Hitbox, color: Color) {
+        let center = hitbox.center(position);
+        let (x, y) = (center.x as f64, center.y as f64);
+        let stroke = Paint::Stroke(color, 1.0);
+        match hitbox.shape {
+            Shape::Circle { radius } => self.backend.draw_circle(x, y, radius as f64, stroke),
+            Shape::Rect { width, height } => {
+                let (width, height) = (width as f64, height as f64);
+                self.backend.draw_rect(x - width / 2.0, y - height / 2.0, width, height, stroke);
+            }
+            Shape::Capsule { radius, half_length } => {
+                // 両端の円と、それをつなぐ左右の辺
+                let (radius, half_length) = (radius as f64, half_length as f64);
+                self.backend.draw_circle(x, y - half_length, radius, stroke);
+                self.backend.draw_circle(x, y + half_length, radius, stroke);
+                for side in [-radius, radius] {
+                    self.backend.draw_line((x + side, y - half_length), (x + side, y + half_length), color, 1.0);
+                }
+            }
+        }
+
+        // 中心の点
+        self.backend.draw_rect(position.x as f64 - 1.5, position.y as f64 - 1.5, 3.0, 3.0, Paint::Fill(color));
+    }
+
+    // グリッドの区切りと、要素が入っているセルの数
+    pub fn draw_debug_grid(&mut self, grid: &SpatialGrid<Handle>) {
+        let size = grid.cell_size() as f64;
+        let yellow = Color::rgb(255, 255, 0);
+        for (column, row, count) in grid.cell_counts() {
+            let (x, y) = (column as f64 * size, row as f64 * size);
+            self.backend.draw_rect(x, y, size, size, Paint::Stroke(Color::WHITE.with_alpha(0.15), 1.0));
+            if count > 0 {
+                self.backend.draw_rect(x, y, size, size, Paint::Fill(yellow.with_alpha(0.1)));
+                self.backend.draw_text(&count.to_string(), x + 3.0, y + 12.0, 10, yellow, TextAlign::Left);
+            }
+        }
+    }
+
+    pub fn draw_debug_text(&mut self, lines: &[String], x: f64, y: f64) {
+        let width = 360.0;
+        let background = Paint::Fill(Color::BLACK.with_alpha(0.6));
+        self.backend.draw_rect(x - 4.0, y - 12.0, width, lines.len() as f64 * 16.0 + 4.0, background);
+        for (i, line) in lines.iter().enumerate() {
+            self.backend.draw_text(line, x, y + i as f64 * 16.0, 12, Color::WHITE, TextAlign::Left);
+        }
+    }
+
+    // フレームの経過時間（灰色）と、そのうちステップにかかった時間（緑）の棒グラフ
+    // 値はミリ秒単位で、横線は 60 FPS のときの1フレームの長さ
+    pub fn draw_debug_graph(&mut self, samples: impl IntoIterator<Item = (f64, f64)>, x: f64, y: f64) {
+        let (width, height) = (240.0, 60.0);
+        let scale = height / 33.3; // 30 FPS で上端に届く
+        self.backend.draw_rect(x - 4.0, y, width + 8.0, height, Paint::Fill(Color::BLACK.with_alpha(0.6)));
+        for (i, (frame_time, tick_time)) in samples.into_iter().enumerate() {
+            let bar_x = x + i as f64 * 2.0;
+            let frame_height = (frame_time * scale).min(height);
+            let tick_height = (tick_time * scale).min(height);
+            self.backend.draw_rect(bar_x, y + height - frame_height, 2.0, frame_height, Paint::Fill(Color::rgb(128, 128, 128)));
+            self.backend.draw_rect(bar_x, y + height - tick_height, 2.0, tick_height, Paint::Fill(Color::rgb(0, 255, 0)));
+        }
+        let budget_y = y + height - 1000.0 / 60.0 * scale;
+        self.backend.draw_rect(x - 4.0, budget_y, width + 8.0, 1.0, Paint::Fill(Color::RED));
+    }
+}
@@ -0,0 +1,108 @@
+// ゲーム専用の疑似乱数生成器（PCG32）
+// シードが同じなら同じ乱数列になるので、デバッグやリプレイ、テストで同じ展開を再現できる
+#[derive(Clone)]
+pub struct Rng {
+    state: u64,
+    seed: u64,
+}
+
+const MULTIPLIER: u64 = 6364136223846793005;
+const INCREMENT: u64 = 1442695040888963407;
+
+impl Rng {
+    pub fn new(seed: u64) -> Rng {
+        let mut rng = Rng { state: 0, seed };
+        rng.next_u32();
+        rng.state = rng.state.wrapping_add(seed);
+        rng.next_u32();
+        rng
+    }
+
+    // 生成に使ったシード
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    pub fn next_u32(&mut self) -> u32 {
+        let old = self.state;
+        self.state = old.wrapping_mul(MULTIPLIER).wrapping_add(INCREMENT);
+        let xorshifted = (((old >> 18) ^ old) >> 27) as u32;
+        let rot = (old >> 59) as u32;
+        xorshifted.rotate_right(rot)
+    }
+
+    // 0.0以上1.0未満の乱数
+    pub fn next_f64(&mut self) -> f64 {
+        self.next_u32() as f64 / (u32::MAX as f64 + 1.0)
+    }
+
+    // min以上max未満の乱数（min と max が同じなら min）
+    pub fn range(&mut self, min: f32, max: f32) -> f32 {
+        lerp_below(min, max, self.next_f64())
+    }
+}
+
+// min から max へ t (0.0以上1.0未満) の割合の値
+// f32 で計算すると丸めで max ちょうどになることがあるので f64 で計算し、
+// それでも f32 にしたときに max になったら max より小さい最大の値にする
+fn lerp_below(min: f32, max: f32, t: f64) -> f32 {
+    let value = (min as f64 + (max as f64 - min as f64) * t) as f32;
+    if value >= max && max > min {
+        max.next_down()
+    } else {
+        value
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn same_seed_gives_same_sequence() {
+        let mut a = Rng::new(42);
+        let mut b = Rng::new(42);
+        for _ in 0..1000 {
+            assert_eq!(a.next_u32(), b.next_u32());
+        }
+    }
+
+    #[test]
+    fn different_seeds_give_different_sequences() {
+        let mut a = Rng::new(1);
+        let mut b = Rng::new(2);
+        let a: Vec<u32> = (0..16).map(|_| a.next_u32()).collect();
+        let b: Vec<u32> = (0..16).map(|_| b.next_u32()).collect();
+        assert_ne!(a, b);
+    }
+
+    #[test]
+    fn range_stays_below_max() {
+        let mut rng = Rng::new(3);
+        for _ in 0..10000 {
+            let value = rng.range(0.0, 800.0);
+            assert!((0.0..800.0).contains(&value), "{}", value);
+        }
+        assert_eq!(rng.range(5.0, 5.0), 5.0);
+    }
+
+    #[test]
+    fn range_does_not_round_up_to_max() {
+        // next_u32 が u32::MAX を返したときの next_f64（f32 にすると 1.0 に丸められる）
+        let largest = u32::MAX as f64 / (u32::MAX as f64 + 1.0);
+        assert_eq!(largest as f32, 1.0);
+        assert!(lerp_below(0.0, 1.0, largest) < 1.0);
+        assert!(lerp_below(0.0, 800.0, largest) < 800.0);
+        assert!(lerp_below(-0.5, 0.5, largest) < 0.5);
+        assert_eq!(lerp_below(0.0, 800.0, 0.0), 0.0);
+    }
+
+    #[test]
+    fn clone_continues_the_same_sequence() {
+        let mut rng = Rng::new(9);
+        rng.next_u32();
+        let mut copy = rng.clone();
+        assert_eq!(rng.next_u32(), copy.next_u32());
+        assert_eq!(copy.seed(), 9);
+    }
+}
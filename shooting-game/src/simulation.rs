@@ -0,0 +1,1038 @@
+use crate::enemy::Enemy;
+use crate::player::Player;
+use crate::bullet::Bullet;
+use crate::input::Input;
+use crate::game_state::GameState;
+use crate::enemy_type::EnemyType;
+use crate::position::Position;
+use crate::stage::{StageRunner, StageScript};
+use crate::rng::Rng;
+use crate::bullet_pattern::{Emitter, PatternLibrary};
+use crate::spatial_grid::SpatialGrid;
+use crate::event::{DespawnReason, EntityKind, GameEvent};
+use crate::lifecycle::{Playfield, DESPAWN_MARGIN};
+use crate::pool::{Handle, Pool};
+use crate::config::GameConfig;
+use crate::explosion::{Explosion, EXPLOSION_CLIP, EXPLOSION_SPRITE};
+use crate::asset_manifest::AssetManifest;
+use crate::background::Background;
+use crate::particle::{ParticleEmitter, ParticleSystem, BULLET_IMPACT, ENEMY_EXPLOSION, ENGINE_EXHAUST, PLAYER_HIT};
+
+const ENEMY_CELL_SIZE: f32 = 128.0; // 敵は大きいので粗く区切る
+
+// プールに最初に確保しておく数
+const BULLET_CAPACITY: usize = 256;
+const ENEMY_BULLET_CAPACITY: usize = 2048;
+const ENEMY_CAPACITY: usize = 64;
+const EXPLOSION_CAPACITY: usize = 32;
+
+// 爆発は敵の大きさよりこれだけ大きく描く
+const EXPLOSION_SCALE: f32 = 1.2;
+
+// ステージクリアとゲームオーバーの画面から結果画面に進むまでの時間（秒単位）
+const RESULTS_DELAY: f64 = 3.0;
+
+// コンティニューしたときにスコアから引く割合
+pub const CONTINUE_PENALTY_RATE: f32 = 0.5;
+
+const PLAYER_START: Position = Position { x: 400.0, y: 500.0 };
+
+// 一度に出すパーティクルの数
+const ENEMY_EXPLOSION_PARTICLES: usize = 40;
+const PLAYER_HIT_PARTICLES: usize = 30;
+const BULLET_IMPACT_PARTICLES: usize = 6;
+
+// エンジンの噴射を出す位置（プレイヤーの中心からのずれ）
+const EXHAUST_OFFSET_Y: f32 = 60.0;
+
+// パーティクルの乱数のシードはゲームのシードからずらして作る
+const PARTICLE_SEED_SALT: u64 = 0x9e37_79b9_7f4a_7c15;
+
+// ブラウザに依存しないゲーム本体
+// 入力と経過時間を受け取って状態を進めるだけで、描画やイベント処理は持たない
+pub struct Simulation {
+    width: f32,
+    height: f32,
+    player: Player,
+    bullets: Pool<Bullet>,
+    enemy_bullets: Pool<Bullet>, // 敵が撃った弾（プレイヤーの弾とは別に管理する）
+    enemies: Pool<Enemy>,
+    explosions: Pool<Explosion>,
+    explosion_duration: f32, // 爆発を表示する時間（描画に使う爆発のアニメーションの長さ）
+    particles: ParticleSystem,
+    exhaust: ParticleEmitter,
+    background: Background,
+    score: u32,
+    state: GameState,
+    state_time: f64, // 今の状態になってからの経過時間（秒単位）
+    previous_input: Input, // キーを押した瞬間を調べるための前のステップの入力
+    time: f64, // ゲーム開始からの経過時間（秒単位）
+    stage: StageRunner,
+    patterns: PatternLibrary,
+    rng: Rng, // ゲーム中の乱数はすべてここから取る
+    enemy_grid: SpatialGrid<Handle>, // 敵の衝突判定の絞り込み用
+    candidates: Vec<Handle>,         // グリッドから取り出した衝突候補
+    removed: Vec<Handle>,            // 衝突判定のあとで消す弾
+    config: GameConfig,
+    escaped: u32, // 逃がした敵の数
+    continues: u32, // コンティニューした回数
+    events: Vec<GameEvent>,
+}
+
+impl Simulation {
+    // seed が同じなら、同じ入力に対して同じ展開になる
+    pub fn new(width: f32, height: f32, seed: u64) -> Simulation {
+        Simulation::with_stage(width, height, seed, StageScript::default())
+    }
+
+    pub fn with_stage(width: f32, height: f32, seed: u64, script: StageScript) -> Simulation {
+        Simulation {
+            width,
+            height,
+            player: Player::new(PLAYER_START.x, PLAYER_START.y),
+            bullets: Pool::with_capacity(BULLET_CAPACITY),
+            enemy_bullets: Pool::with_capacity(ENEMY_BULLET_CAPACITY),
+            enemies: Pool::with_capacity(ENEMY_CAPACITY),
+            explosions: Pool::with_capacity(EXPLOSION_CAPACITY),
+            explosion_duration: AssetManifest::built_in()
+                .clip_duration(EXPLOSION_SPRITE, EXPLOSION_CLIP)
+                .expect("built-in asset manifest should declare the explosion animation"),
+            particles: ParticleSystem::new(seed ^ PARTICLE_SEED_SALT),
+            exhaust: ParticleEmitter::new(&ENGINE_EXHAUST),
+            background: Background::new(&script.background, width, height),
+            score: 0,
+            state: GameState::Title,
+            state_time: 0.0,
+            previous_input: Input::default(),
+            time: 0.0,
+            patterns: script.pattern_library(),
+            stage: StageRunner::new(script),
+            rng: Rng::new(seed),
+            enemy_grid: SpatialGrid::new(width, height, ENEMY_CELL_SIZE),
+            candidates: Vec::new(),
+            removed: Vec::new(),
+            config: GameConfig::default(),
+            escaped: 0,
+            continues: 0,
+            events: Vec::new(),
+        }
+    }
+
+    // ゲームが始まる前ならすぐに、そうでなければ次のゲームから反映される
+    pub fn set_config(&mut self, config: GameConfig) {
+        self.config = config;
+        if self.state == GameState::Title {
+            self.player = self.spawn_player();
+        }
+    }
+
+    pub fn config(&self) -> &GameConfig {
+        &self.config
+    }
+
+    // 爆発を表示する時間を、描画に使うアニメーションの長さに合わせる
+    // 組み込みの assets.json とは違う素材（atlas など）で描くときに呼ぶ。すでに出ている爆発には効かない
+    pub fn set_explosion_duration(&mut self, seconds: f32) {
+        self.explosion_duration = seconds;
+    }
+
+    // 入力と秒単位の刻み幅を受け取ってゲームを1ステップ進める
+    // 速度はすべて1秒あたりの量なので、dt を固定すればフレームレートに関係なく同じ動きになる
+    pub fn step(&mut self, input: &Input, dt: f64) {
+        // Enter、Escape、C は押しっぱなしで状態が行き来しないよう、押した瞬間だけ反応する
+        let start_pressed = input.start && !self.previous_input.start;
+        let pause_pressed = input.pause && !self.previous_input.pause;
+        let continue_pressed = input.continue_game && !self.previous_input.continue_game;
+        self.previous_input = *input;
+        self.state_time += dt;
+
+        // 背景は一時停止中を除いて、読み込み中やタイトル画面、結果画面でも流し続ける
+        if self.state != GameState::Paused {
+            self.background.update(dt as f32);
+        }
+
+        match self.state {
+            GameState::Loading => {}
+            GameState::Title => {
+                if start_pressed {
+                    self.start();
+                }
+            }
+            GameState::Playing => {
+                if pause_pressed {
+                    self.set_state(GameState::Paused);
+                } else {
+                    self.update_playing(input, dt);
+                }
+            }
+            GameState::Paused => {
+                if pause_pressed || start_pressed {
+                    self.set_state(GameState::Playing);
+                }
+            }
+            GameState::StageClear | GameState::GameOver => {
+                if continue_pressed && self.can_continue() {
+                    self.continue_game();
+                } else if start_pressed || self.state_time >= RESULTS_DELAY {
+                    self.set_state(GameState::Results);
+                }
+            }
+            GameState::Results => {
+                if continue_pressed && self.can_continue() {
+                    self.continue_game();
+                } else if start_pressed {
+                    // 次のゲームのシードは今の乱数から作るので、同じ展開の繰り返しにはならない
+                    let seed = self.rng.next_u32() as u64;
+                    self.reset(seed);
+                    self.start();
+                }
+            }
+        }
+    }
+
+    // 素材を読み込んでいる間は Loading にしてゲームを始められないようにする
+    // 読み込みが終わったら false で呼んでタイトル画面に進める
+    pub fn set_loading(&mut self, loading: bool) {
+        if loading && self.state == GameState::Title {
+            self.set_state(GameState::Loading);
+        } else if !loading && self.state == GameState::Loading {
+            self.set_state(GameState::Title);
+        }
+    }
+
+    // タイトル画面からゲームを始める
+    pub fn start(&mut self) {
+        if self.state == GameState::Title {
+            self.events.push(GameEvent::GameStarted { seed: self.rng.seed() });
+            self.set_state(GameState::Playing);
+        }
+    }
+
+    // 一時停止する（プレイ中でなければ何もしない）
+    pub fn pause(&mut self) {
+        if self.state == GameState::Playing {
+            self.set_state(GameState::Paused);
+        }
+    }
+
+    // 一時停止から再開する
+    pub fn resume(&mut self) {
+        if self.state == GameState::Paused {
+            self.set_state(GameState::Playing);
+        }
+    }
+
+    // プレイヤー、弾、敵、スコア、ステージの進行を最初に戻してタイトル画面にする
+    // プールやグリッド、撃ち方の一覧は確保したものをそのまま使い回す
+    pub fn reset(&mut self, seed: u64) {
+        self.player = self.spawn_player();
+        self.bullets.clear();
+        self.enemy_bullets.clear();
+        self.enemies.clear();
+        self.explosions.clear();
+        self.particles.reset(seed ^ PARTICLE_SEED_SALT);
+        self.score = 0;
+        self.time = 0.0;
+        self.stage.reset();
+        self.background.reset();
+        self.rng = Rng::new(seed);
+        self.escaped = 0;
+        self.continues = 0;
+        // 素材の読み込み中ならそのまま待つ
+        if self.state != GameState::Loading {
+            self.set_state(GameState::Title);
+        }
+    }
+
+    fn spawn_player(&self) -> Player {
+        Player::new(PLAYER_START.x, PLAYER_START.y).with_life(self.config.starting_lives)
+    }
+
+    // ゲームオーバーのあとでコンティニューできるかどうか
+    pub fn can_continue(&self) -> bool {
+        matches!(self.state, GameState::GameOver | GameState::Results) && self.player.get_life() == 0
+    }
+
+    // スコアを減らす代わりに、ステージの進行とスコアを残したままライフを戻して再開する
+    fn continue_game(&mut self) {
+        let penalty = (self.score as f32 * CONTINUE_PENALTY_RATE) as u32;
+        self.score -= penalty;
+        self.continues += 1;
+        self.player.restore_life();
+        // 再開した瞬間に当たらないよう、プレイヤーを最初の位置に戻し、
+        // 画面上の敵の弾と、戻した位置でプレイヤーに重なっている敵は消しておく
+        self.player.move_to(PLAYER_START);
+        self.enemy_bullets.clear();
+        let player_hitbox = *self.player.get_hitbox();
+        self.enemies.retain(|_, enemy| {
+            !player_hitbox.intersects(PLAYER_START, enemy.get_hitbox(), enemy.get_position())
+        });
+        self.events.push(GameEvent::Continued { penalty });
+        self.set_state(GameState::Playing);
+    }
+
+    fn set_state(&mut self, state: GameState) {
+        self.events.push(GameEvent::StateChanged { from: self.state, to: state });
+        self.state = state;
+        self.state_time = 0.0;
+    }
+
+    fn update_playing(&mut self, input: &Input, dt: f64) {
+        self.time += dt;
+        self.player.save_position();
+        self.handle_input(input, dt as f32);
+
+        self.spawn_enemies(dt);
+
+        self.update(dt as f32);
+
+        // 最後のウェーブを出し終えて敵がいなくなったらステージクリア
+        if self.state == GameState::Playing && self.stage.is_finished() && self.is_field_cleared() {
+            self.set_state(GameState::StageClear);
+        }
+    }
+
+    fn handle_input(&mut self, input: &Input, dt: f32) {
+        if let Some(pattern) = input.weapon {
+            self.player.weapon_mut().set_pattern(pattern);
+        }
+
+        // スペースバーを押している間は連射する
+        self.player.fire(dt, input.fire, &mut self.bullets);
+
+        // 押されている矢印キーの方向にプレイヤーを移動
+        self.player.update(input.direction_x(), input.direction_y(), dt, self.width, self.height);
+    }
+
+    // ステージスクリプトに従って敵を出現させる
+    fn spawn_enemies(&mut self, dt: f64) {
+        let field_cleared = self.is_field_cleared();
+        let requests = self.stage.update(dt, field_cleared, self.width, &mut self.rng);
+
+        for request in requests {
+            let emitter = request
+                .pattern
+                .and_then(|name| self.patterns.get(&name).cloned())
+                .map(|pattern| Emitter::new(pattern, request.fire_interval * self.config.difficulty.fire_interval_scale()));
+            self.spawn_enemy(request.enemy_type, request.position, emitter);
+        }
+    }
+
+    fn spawn_enemy(&mut self, enemy_type: EnemyType, position: Position, emitter: Option<Emitter>) {
+        if self.state != GameState::Playing {
+            return;
+        }
+
+        let mut enemy = Enemy::new(enemy_type, position.x, position.y)
+            .with_speed_scale(self.config.difficulty.enemy_speed_scale());
+        if let Some(emitter) = emitter {
+            enemy = enemy.with_emitter(emitter);
+        }
+        self.enemies.spawn(enemy);
+    }
+
+    // 画面内（とこれから画面に入ってくる位置）に敵が残っていないかどうか
+    fn is_field_cleared(&self) -> bool {
+        self.enemies.is_empty()
+    }
+
+    fn update(&mut self, dt: f32) {
+        for bullet in self.bullets.values_mut() {
+            bullet.update(dt);
+        }
+
+        for enemy in self.enemies.values_mut() {
+            enemy.move_down(dt);
+        }
+
+        // 画面に入っている敵だけがプレイヤーを狙って撃つ
+        let target = self.player.get_position();
+        for enemy in self.enemies.values_mut() {
+            if enemy.position.y >= 0.0 && enemy.position.y <= self.height {
+                enemy.fire(dt, target, &mut self.rng, &mut self.enemy_bullets);
+            }
+        }
+
+        for bullet in self.enemy_bullets.values_mut() {
+            bullet.update(dt);
+        }
+
+        // 爆発は表示し終えたら消す
+        self.explosions.retain(|_, explosion| {
+            explosion.update(dt);
+            !explosion.is_finished()
+        });
+
+        self.particles.update(dt);
+        let exhaust_position = Position::new(self.player.position.x, self.player.position.y + EXHAUST_OFFSET_Y);
+        self.exhaust.update(dt, exhaust_position, &mut self.particles);
+
+        self.despawn_outside();
+        self.check_collisions();
+    }
+
+    // 画面の外に出た弾と敵を消す
+    fn despawn_outside(&mut self) {
+        let playfield = Playfield {
+            width: self.width,
+            height: self.height,
+            margin: DESPAWN_MARGIN,
+        };
+        let events = &mut self.events;
+
+        for (bullets, kind) in [
+            (&mut self.bullets, EntityKind::PlayerBullet),
+            (&mut self.enemy_bullets, EntityKind::EnemyBullet),
+        ] {
+            bullets.retain(|_, bullet| {
+                let outside = playfield.is_outside(bullet.position, bullet.width, bullet.height);
+                if outside {
+                    events.push(GameEvent::Despawned {
+                        kind,
+                        reason: DespawnReason::LeftPlayfield,
+                        position: bullet.position,
+                    });
+                }
+                !outside
+            });
+        }
+
+        // 敵は画面の上から入ってくるので、左右と下に出たときだけ消す
+        let penalty = self.config.escape_penalty;
+        let mut escaped = 0;
+        self.enemies.retain(|_, enemy| {
+            let past_bottom = playfield.is_past_bottom(enemy.position, enemy.height);
+            let outside = past_bottom || playfield.is_past_sides(enemy.position, enemy.width);
+            if outside {
+                events.push(GameEvent::Despawned {
+                    kind: EntityKind::Enemy(enemy.get_enemy_type()),
+                    reason: DespawnReason::LeftPlayfield,
+                    position: enemy.position,
+                });
+            }
+            if past_bottom {
+                // 下から出た敵は倒し損ねたものとして数える
+                escaped += 1;
+                events.push(GameEvent::EnemyEscaped { enemy_type: enemy.get_enemy_type(), penalty });
+            }
+            !outside
+        });
+        self.escaped += escaped;
+        self.score = self.score.saturating_sub(penalty * escaped);
+    }
+
+    // 衝突判定をチェックするメイン関数
+    fn check_collisions(&mut self) {
+        // 敵をグリッドに入れて、弾と敵、プレイヤーと敵の判定で共有する
+        self.enemy_grid.clear();
+        for (handle, enemy) in self.enemies.iter() {
+            self.enemy_grid.insert(handle, enemy.get_position(), enemy.get_hitbox());
+        }
+
+        // 弾と敵の衝突判定
+        self.check_bullet_enemy_collisions();
+
+        // プレイヤーと敵の衝突判定
+        self.check_player_enemy_collisions();
+
+        // 敵の弾とプレイヤーの衝突判定
+        self.check_enemy_bullet_player_collisions();
+
+        // プレイヤーのライフが0ならゲームオーバー
+        if self.player.get_life() == 0 {
+            self.set_state(GameState::GameOver);
+        }
+    }
+
+    // 弾と敵の衝突判定
+    // 倒した敵はすぐにプールから消えるので、グリッドに残っていても get_mut で None になる
+    fn check_bullet_enemy_collisions(&mut self) {
+        for (bullet_handle, bullet) in self.bullets.iter() {
+            let bullet_position = bullet.get_position();
+            self.enemy_grid.query(bullet_position, bullet.get_hitbox(), &mut self.candidates);
+
+            for &enemy_handle in &self.candidates {
+                let Some(enemy) = self.enemies.get_mut(enemy_handle) else {
+                    continue;
+                };
+                if !bullet.get_hitbox().intersects(bullet_position, enemy.get_hitbox(), enemy.get_position()) {
+                    continue;
+                }
+
+                // 衝突した場合、弾を削除して敵にダメージを与える
+                // 敵のライフが0になったら削除してスコアを加算
+                self.particles.burst(&BULLET_IMPACT, bullet_position, BULLET_IMPACT_PARTICLES);
+                if enemy.take_damage(bullet.get_damage()) {
+                    self.score += enemy.get_score();
+                    self.explosions.spawn(Explosion::new(enemy.get_position(), enemy.width * EXPLOSION_SCALE, self.explosion_duration));
+                    self.particles.burst(&ENEMY_EXPLOSION, enemy.get_position(), ENEMY_EXPLOSION_PARTICLES);
+                    self.events.push(GameEvent::Despawned {
+                        kind: EntityKind::Enemy(enemy.get_enemy_type()),
+                        reason: DespawnReason::Destroyed,
+                        position: enemy.get_position(),
+                    });
+                    self.enemies.despawn(enemy_handle);
+                }
+                self.removed.push(bullet_handle);
+                self.events.push(GameEvent::Despawned {
+                    kind: EntityKind::PlayerBullet,
+                    reason: DespawnReason::Collided,
+                    position: bullet_position,
+                });
+                break; // 1つの弾が複数の敵に当たらないように
+            }
+        }
+
+        for handle in self.removed.drain(..) {
+            self.bullets.despawn(handle);
+        }
+    }
+
+    // 同じステップで複数の敵にぶつかっても、減るライフは1つだけ（ぶつかった敵はすべて消す）
+    fn check_player_enemy_collisions(&mut self) {
+        if self.player.get_life() == 0 {
+            return;
+        }
+        let player_position = self.player.get_position();
+        let player_hitbox = *self.player.get_hitbox();
+
+        let mut hit = false;
+        self.enemy_grid.query(player_position, &player_hitbox, &mut self.candidates);
+        for &handle in &self.candidates {
+            let Some(enemy) = self.enemies.get(handle) else {
+                continue;
+            };
+            if player_hitbox.intersects(player_position, enemy.get_hitbox(), enemy.get_position()) {
+                // 衝突した場合、プレイヤーのライフを減らし、敵を削除
+                if !hit {
+                    hit = true;
+                    self.player.decrease_life();
+                    self.events.push(GameEvent::LifeLost { life: self.player.get_life() });
+                    self.particles.burst(&PLAYER_HIT, player_position, PLAYER_HIT_PARTICLES);
+                }
+                self.explosions.spawn(Explosion::new(enemy.get_position(), enemy.width * EXPLOSION_SCALE, self.explosion_duration));
+                self.particles.burst(&ENEMY_EXPLOSION, enemy.get_position(), ENEMY_EXPLOSION_PARTICLES);
+                self.events.push(GameEvent::Despawned {
+                    kind: EntityKind::Enemy(enemy.get_enemy_type()),
+                    reason: DespawnReason::Collided,
+                    position: enemy.get_position(),
+                });
+                self.enemies.despawn(handle);
+            }
+        }
+    }
+
+    // 敵の弾はプレイヤーの小さな当たり判定とだけ判定する
+    // 相手が1つだけなのでグリッドを作るより順に見ていくほうが速い（benches/collision.rs）
+    fn check_enemy_bullet_player_collisions(&mut self) {
+        // ライフが0になったあとは当たっても数えない（このステップの最後にゲームオーバーになる）
+        if self.player.get_life() == 0 {
+            return;
+        }
+
+        let player_position = self.player.get_position();
+        let core_hitbox = *self.player.get_core_hitbox();
+        let player = &mut self.player;
+        let particles = &mut self.particles;
+        let events = &mut self.events;
+
+        self.enemy_bullets.retain(|_, bullet| {
+            if player.get_life() > 0 && core_hitbox.intersects(player_position, bullet.get_hitbox(), bullet.get_position()) {
+                // 当たった弾を消してライフを減らす
+                player.decrease_life();
+                particles.burst(&PLAYER_HIT, player_position, PLAYER_HIT_PARTICLES);
+                events.push(GameEvent::LifeLost { life: player.get_life() });
+                events.push(GameEvent::Despawned {
+                    kind: EntityKind::EnemyBullet,
+                    reason: DespawnReason::Collided,
+                    position: bullet.get_position(),
+                });
+                false
+            } else {
+                true
+            }
+        });
+    }
+
+    // 前回呼び出してから起きた出来事を取り出す
+    pub fn drain_events(&mut self) -> std::vec::Drain<'_, GameEvent> {
+        self.events.drain(..)
+    }
+
+    pub fn escaped(&self) -> u32 {
+        self.escaped
+    }
+
+    pub fn continues(&self) -> u32 {
+        self.continues
+    }
+
+    pub fn width(&self) -> f32 {
+        self.width
+    }
+
+    pub fn height(&self) -> f32 {
+        self.height
+    }
+
+    pub fn player(&self) -> &Player {
+        &self.player
+    }
+
+    pub fn bullets(&self) -> &Pool<Bullet> {
+        &self.bullets
+    }
+
+    pub fn enemy_bullets(&self) -> &Pool<Bullet> {
+        &self.enemy_bullets
+    }
+
+    pub fn enemies(&self) -> &Pool<Enemy> {
+        &self.enemies
+    }
+
+    pub fn explosions(&self) -> &Pool<Explosion> {
+        &self.explosions
+    }
+
+    pub fn particles(&self) -> &ParticleSystem {
+        &self.particles
+    }
+
+    pub fn background(&self) -> &Background {
+        &self.background
+    }
+
+    // 最後のステップで敵を入れたグリッド（デバッグ表示用）
+    pub fn enemy_grid(&self) -> &SpatialGrid<Handle> {
+        &self.enemy_grid
+    }
+
+    pub fn score(&self) -> u32 {
+        self.score
+    }
+
+    pub fn time(&self) -> f64 {
+        self.time
+    }
+
+    pub fn stage(&self) -> &StageRunner {
+        &self.stage
+    }
+
+    pub fn seed(&self) -> u64 {
+        self.rng.seed()
+    }
+
+    pub fn state(&self) -> GameState {
+        self.state
+    }
+
+    // 描画に使う補間係数（timestep_alpha は FixedTimestep::alpha）
+    // 止まっている間は補間すると前のステップとの間で揺れるので、最後の位置で描く
+    pub fn render_alpha(&self, timestep_alpha: f64) -> f32 {
+        if self.state == GameState::Playing {
+            timestep_alpha as f32
+        } else {
+            1.0
+        }
+    }
+
+    pub fn state_time(&self) -> f64 {
+        self.state_time
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DT: f64 = 1.0 / 60.0;
+
+    fn playing(seed: u64) -> Simulation {
+        let mut simulation = Simulation::new(800.0, 600.0, seed);
+        simulation.start();
+        simulation.drain_events().for_each(drop);
+        simulation
+    }
+
+    #[test]
+    fn step_moves_player_with_held_input() {
+        let mut simulation = playing(1);
+        let start = simulation.player().get_position();
+
+        let right = Input { right: true, ..Input::default() };
+        for _ in 0..30 {
+            simulation.step(&right, DT);
+        }
+        let moved = simulation.player().get_position();
+        assert!(moved.x > start.x, "{:?} should be right of {:?}", moved, start);
+        assert_eq!(moved.y, start.y);
+
+        let up = Input { up: true, ..Input::default() };
+        for _ in 0..30 {
+            simulation.step(&up, DT);
+        }
+        assert!(simulation.player().get_position().y < moved.y);
+    }
+
+    #[test]
+    fn step_does_not_move_player_while_paused() {
+        let mut simulation = playing(1);
+        simulation.pause();
+        let start = simulation.player().get_position();
+        simulation.step(&Input { left: true, ..Input::default() }, DT);
+        assert_eq!(simulation.player().get_position(), start);
+    }
+
+    #[test]
+    fn bullet_destroys_enemy_and_scores() {
+        let mut simulation = playing(1);
+        let enemy = simulation.enemies.spawn(Enemy::new(EnemyType::Regular, 400.0, 200.0));
+        simulation.bullets.spawn(Bullet::new(400.0, 220.0));
+
+        simulation.step(&Input::default(), DT);
+
+        assert!(!simulation.enemies().contains(enemy));
+        assert!(simulation.bullets().is_empty());
+        assert_eq!(simulation.score(), EnemyType::Regular.stats().score);
+        let events: Vec<GameEvent> = simulation.drain_events().collect();
+        assert!(events.iter().any(|event| matches!(
+            event,
+            GameEvent::Despawned { kind: EntityKind::Enemy(EnemyType::Regular), reason: DespawnReason::Destroyed, .. }
+        )));
+        assert!(events.iter().any(|event| matches!(
+            event,
+            GameEvent::Despawned { kind: EntityKind::PlayerBullet, reason: DespawnReason::Collided, .. }
+        )));
+    }
+
+    #[test]
+    fn bullet_that_misses_does_not_score() {
+        let mut simulation = playing(1);
+        let enemy = simulation.enemies.spawn(Enemy::new(EnemyType::Regular, 100.0, 200.0));
+        simulation.bullets.spawn(Bullet::new(700.0, 220.0));
+
+        simulation.step(&Input::default(), DT);
+
+        assert!(simulation.enemies().contains(enemy));
+        assert_eq!(simulation.bullets().len(), 1);
+        assert_eq!(simulation.score(), 0);
+    }
+
+    #[test]
+    fn continue_does_not_lose_a_life_to_overlapping_enemies() {
+        let mut simulation = playing(1);
+        simulation.player.move_to(Position::new(200.0, 300.0));
+        for _ in 0..simulation.player().get_life() {
+            simulation.player.decrease_life();
+        }
+        simulation.step(&Input::default(), DT);
+        assert_eq!(simulation.state(), GameState::GameOver);
+
+        // プレイヤーがいた場所と、戻る場所の両方に敵がいる
+        simulation.enemies.spawn(Enemy::new(EnemyType::Strong, 200.0, 300.0));
+        let at_start = simulation.enemies.spawn(Enemy::new(EnemyType::Regular, PLAYER_START.x, PLAYER_START.y));
+        let far = simulation.enemies.spawn(Enemy::new(EnemyType::Regular, 700.0, 100.0));
+        simulation.enemy_bullets.spawn(Bullet::new(PLAYER_START.x, PLAYER_START.y));
+
+        simulation.step(&Input { continue_game: true, ..Input::default() }, DT);
+        assert_eq!(simulation.state(), GameState::Playing);
+        assert_eq!(simulation.player().get_position(), PLAYER_START);
+        assert!(!simulation.enemies().contains(at_start));
+        assert!(simulation.enemies().contains(far));
+        assert!(simulation.enemy_bullets().is_empty());
+
+        let life = simulation.player().get_life();
+        simulation.step(&Input::default(), DT);
+        assert_eq!(simulation.player().get_life(), life);
+        assert!(!simulation.drain_events().any(|event| matches!(event, GameEvent::LifeLost { .. })));
+    }
+
+    #[test]
+    fn explosion_lasts_as_long_as_its_animation() {
+        let mut simulation = playing(1);
+        simulation.set_explosion_duration(0.5);
+        simulation.enemies.spawn(Enemy::new(EnemyType::Regular, 400.0, 200.0));
+        simulation.bullets.spawn(Bullet::new(400.0, 220.0));
+        simulation.step(&Input::default(), DT);
+        assert_eq!(simulation.explosions().len(), 1);
+
+        // 0.5秒（30ステップ）目までは残り、それを過ぎたら消える
+        for _ in 0..29 {
+            simulation.step(&Input::default(), DT);
+        }
+        assert_eq!(simulation.explosions().len(), 1);
+        simulation.step(&Input::default(), DT);
+        simulation.step(&Input::default(), DT);
+        assert!(simulation.explosions().is_empty());
+    }
+
+    #[test]
+    fn explosion_duration_defaults_to_the_built_in_clip() {
+        let simulation = Simulation::new(800.0, 600.0, 1);
+        let clip = AssetManifest::built_in().clip_duration(EXPLOSION_SPRITE, EXPLOSION_CLIP).unwrap();
+        assert_eq!(simulation.explosion_duration, clip);
+    }
+
+    fn playing_with_lives(lives: u32) -> Simulation {
+        let mut simulation = Simulation::new(800.0, 600.0, 1);
+        simulation.set_config(GameConfig { starting_lives: lives, ..GameConfig::default() });
+        simulation.start();
+        simulation.drain_events().for_each(drop);
+        simulation
+    }
+
+    fn lives_lost(events: &[GameEvent]) -> Vec<u32> {
+        events
+            .iter()
+            .filter_map(|event| match event {
+                GameEvent::LifeLost { life } => Some(*life),
+                _ => None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn enemy_bullets_stop_hitting_once_the_last_life_is_gone() {
+        let mut simulation = playing_with_lives(1);
+        for _ in 0..3 {
+            simulation.enemy_bullets.spawn(Bullet::new(PLAYER_START.x, PLAYER_START.y));
+        }
+
+        simulation.step(&Input::default(), DT);
+
+        let events: Vec<GameEvent> = simulation.drain_events().collect();
+        assert_eq!(lives_lost(&events), [0]);
+        assert_eq!(simulation.state(), GameState::GameOver);
+        assert_eq!(simulation.enemy_bullets().len(), 2);
+    }
+
+    #[test]
+    fn overlapping_enemies_cost_one_life_per_step() {
+        let mut simulation = playing_with_lives(3);
+        for offset in [-10.0, 10.0] {
+            simulation.enemies.spawn(Enemy::new(EnemyType::Regular, PLAYER_START.x + offset, PLAYER_START.y));
+        }
+
+        simulation.step(&Input::default(), DT);
+
+        let events: Vec<GameEvent> = simulation.drain_events().collect();
+        assert_eq!(lives_lost(&events), [2]);
+        assert!(simulation.enemies().is_empty());
+        assert_eq!(simulation.state(), GameState::Playing);
+
+        // 最後のライフのときは、敵の弾にも当たっていても LifeLost は1回だけ
+        let mut simulation = playing_with_lives(1);
+        for offset in [-10.0, 10.0] {
+            simulation.enemies.spawn(Enemy::new(EnemyType::Regular, PLAYER_START.x + offset, PLAYER_START.y));
+        }
+        simulation.enemy_bullets.spawn(Bullet::new(PLAYER_START.x, PLAYER_START.y));
+        simulation.step(&Input::default(), DT);
+        let events: Vec<GameEvent> = simulation.drain_events().collect();
+        assert_eq!(lives_lost(&events), [0]);
+        assert_eq!(simulation.state(), GameState::GameOver);
+    }
+
+    #[test]
+    fn render_alpha_is_one_unless_playing() {
+        let mut simulation = Simulation::new(800.0, 600.0, 1);
+        assert_eq!(simulation.render_alpha(0.25), 1.0);
+        simulation.start();
+        assert_eq!(simulation.render_alpha(0.25), 0.25);
+        simulation.pause();
+        assert_eq!(simulation.render_alpha(0.25), 1.0);
+    }
+
+    #[test]
+    fn entities_leaving_the_playfield_are_despawned() {
+        let mut simulation = playing(1);
+        simulation.bullets.spawn(Bullet::new(400.0, -DESPAWN_MARGIN - 100.0));
+        simulation.enemies.spawn(Enemy::new(EnemyType::Regular, -DESPAWN_MARGIN - 200.0, 300.0));
+        simulation.enemies.spawn(Enemy::new(EnemyType::Regular, 400.0, 600.0 + DESPAWN_MARGIN + 200.0));
+
+        simulation.step(&Input::default(), DT);
+
+        assert!(simulation.bullets().is_empty());
+        assert!(simulation.enemies().is_empty());
+        let events: Vec<GameEvent> = simulation.drain_events().collect();
+        let left = events
+            .iter()
+            .filter(|event| matches!(event, GameEvent::Despawned { reason: DespawnReason::LeftPlayfield, .. }))
+            .count();
+        assert_eq!(left, 3);
+        // 下から出た敵だけを逃がした数に入れる（減点は既定で0）
+        let escaped: Vec<u32> = events
+            .iter()
+            .filter_map(|event| match event {
+                GameEvent::EnemyEscaped { penalty, .. } => Some(*penalty),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(escaped, [0]);
+        assert_eq!(simulation.score(), 0);
+    }
+
+    #[test]
+    fn escaped_enemies_cost_the_configured_penalty() {
+        let mut simulation = Simulation::new(800.0, 600.0, 1);
+        simulation.set_config(GameConfig { escape_penalty: 30, ..GameConfig::default() });
+        simulation.start();
+        simulation.score = 50;
+        for x in [300.0, 500.0] {
+            simulation.enemies.spawn(Enemy::new(EnemyType::Regular, x, 600.0 + DESPAWN_MARGIN + 200.0));
+        }
+
+        simulation.step(&Input::default(), DT);
+
+        // スコアは0より下がらない
+        assert_eq!(simulation.score(), 0);
+        assert!(simulation
+            .drain_events()
+            .any(|event| matches!(event, GameEvent::EnemyEscaped { penalty: 30, .. })));
+    }
+
+    // input で1ステップ進めてから、何も押さずに1ステップ進める（押した瞬間として扱われるように）
+    fn press(simulation: &mut Simulation, input: Input) {
+        simulation.step(&input, DT);
+        simulation.step(&Input::default(), DT);
+    }
+
+    #[test]
+    fn enter_on_the_title_starts_the_game() {
+        let mut simulation = Simulation::new(800.0, 600.0, 1);
+        simulation.step(&Input::default(), DT);
+        assert_eq!(simulation.state(), GameState::Title);
+
+        simulation.step(&Input { start: true, ..Input::default() }, DT);
+        assert_eq!(simulation.state(), GameState::Playing);
+        assert!(simulation.drain_events().any(|event| matches!(event, GameEvent::GameStarted { seed: 1 })));
+
+        // 押しっぱなしでは一時停止などに進まない
+        simulation.step(&Input { start: true, ..Input::default() }, DT);
+        assert_eq!(simulation.state(), GameState::Playing);
+    }
+
+    #[test]
+    fn escape_and_enter_toggle_pause() {
+        let mut simulation = playing(1);
+        let pause = Input { pause: true, ..Input::default() };
+        let start = Input { start: true, ..Input::default() };
+
+        press(&mut simulation, pause);
+        assert_eq!(simulation.state(), GameState::Paused);
+        let time = simulation.time();
+        simulation.step(&Input::default(), DT);
+        assert_eq!(simulation.time(), time);
+
+        simulation.step(&pause, DT);
+        assert_eq!(simulation.state(), GameState::Playing);
+        // 押しっぱなしではもう一度止まらない
+        simulation.step(&pause, DT);
+        assert_eq!(simulation.state(), GameState::Playing);
+
+        simulation.step(&Input::default(), DT);
+        press(&mut simulation, pause);
+        assert_eq!(simulation.state(), GameState::Paused);
+        press(&mut simulation, start);
+        assert_eq!(simulation.state(), GameState::Playing);
+    }
+
+    #[test]
+    fn clearing_the_last_wave_clears_the_stage() {
+        let script = StageScript::from_json(r#"{ "name": "test", "waves": [
+            { "enemy_type": "Regular", "count": 1, "delay": 0.0 }
+        ] }"#).unwrap();
+        let mut simulation = Simulation::with_stage(800.0, 600.0, 1, script);
+        simulation.start();
+
+        simulation.step(&Input::default(), DT);
+        assert_eq!(simulation.enemies().len(), 1);
+        assert_eq!(simulation.state(), GameState::Playing);
+
+        simulation.enemies.clear();
+        simulation.step(&Input::default(), DT);
+        assert_eq!(simulation.state(), GameState::StageClear);
+        assert!(!simulation.can_continue());
+
+        // 時間がたつと結果画面に進む
+        for _ in 0..(RESULTS_DELAY / DT) as usize + 1 {
+            simulation.step(&Input::default(), DT);
+        }
+        assert_eq!(simulation.state(), GameState::Results);
+    }
+
+    #[test]
+    fn game_over_moves_to_results_after_the_delay() {
+        let mut simulation = playing_with_lives(1);
+        simulation.enemy_bullets.spawn(Bullet::new(PLAYER_START.x, PLAYER_START.y));
+        simulation.step(&Input::default(), DT);
+        assert_eq!(simulation.state(), GameState::GameOver);
+
+        let steps = (RESULTS_DELAY / DT) as usize;
+        for _ in 0..steps - 10 {
+            simulation.step(&Input::default(), DT);
+        }
+        assert_eq!(simulation.state(), GameState::GameOver);
+        for _ in 0..20 {
+            simulation.step(&Input::default(), DT);
+        }
+        assert_eq!(simulation.state(), GameState::Results);
+        assert!(simulation.can_continue());
+    }
+
+    #[test]
+    fn enter_on_the_results_starts_a_fresh_game() {
+        let mut simulation = playing_with_lives(1);
+        simulation.score = 120;
+        simulation.continues = 1;
+        simulation.escaped = 2;
+        for _ in 0..300 {
+            simulation.step(&Input::default(), DT);
+        }
+        simulation.bullets.spawn(Bullet::new(100.0, 100.0));
+        simulation.enemies.spawn(Enemy::new(EnemyType::Regular, 700.0, 100.0));
+        simulation.enemy_bullets.spawn(Bullet::new(PLAYER_START.x, PLAYER_START.y));
+        simulation.step(&Input::default(), DT);
+        simulation.step(&Input { start: true, ..Input::default() }, DT);
+        assert_eq!(simulation.state(), GameState::Results);
+        assert!(simulation.stage().wave_index() > 0 || simulation.stage().spawned() > 0);
+        simulation.drain_events().for_each(drop);
+
+        simulation.step(&Input::default(), DT);
+        simulation.step(&Input { start: true, ..Input::default() }, DT);
+
+        assert_eq!(simulation.state(), GameState::Playing);
+        assert!(simulation.drain_events().any(|event| matches!(event, GameEvent::GameStarted { .. })));
+        assert_eq!(simulation.score(), 0);
+        assert_eq!(simulation.player().get_life(), 1);
+        assert_eq!(simulation.player().get_position(), PLAYER_START);
+        assert_eq!((simulation.escaped(), simulation.continues()), (0, 0));
+        assert_eq!((simulation.stage().wave_index(), simulation.stage().spawned()), (0, 0));
+        assert_eq!(simulation.time(), 0.0);
+        assert!(simulation.bullets().is_empty());
+        assert!(simulation.enemies().is_empty());
+        assert!(simulation.enemy_bullets().is_empty());
+        assert!(simulation.explosions().is_empty());
+    }
+
+    // 各ステップで画面にいる敵の位置
+    fn enemy_positions(seed: u64, ticks: u32) -> Vec<Vec<Position>> {
+        let mut simulation = playing(seed);
+        (0..ticks)
+            .map(|_| {
+                simulation.step(&Input::default(), DT);
+                simulation.enemies().values().map(|enemy| enemy.get_position()).collect()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn same_seed_spawns_enemies_at_same_positions() {
+        // 最初のウェーブはランダムな位置に出る
+        let first = enemy_positions(7, 600);
+        assert!(first.iter().any(|positions| !positions.is_empty()));
+        assert_eq!(first, enemy_positions(7, 600));
+        assert_ne!(first, enemy_positions(8, 600));
+    }
+}
@@ -0,0 +1,245 @@
+use std::collections::HashMap;
+use std::path::Path;
+use anyhow::{Context, Result};
+use image::{Rgba, RgbaImage};
+use crate::asset_manifest::AssetManifest;
+use crate::bitmap_font;
+use crate::render_backend::{BlendMode, Color, Dest, Paint, RenderBackend, TextAlign};
+use crate::sprite_atlas::Rect;
+
+// RgbaImage に描き込む、ブラウザなしで動く描画先
+// 画像の拡大縮小は最近傍、図形はピクセルの中心が内側にあるかどうかで塗る（アンチエイリアスはしない）
+// 文字は組み込みの 5x7 ドットのフォント（bitmap_font.rs）で描く
+pub struct SoftwareBackend {
+    pixels: RgbaImage,
+    images: HashMap<String, RgbaImage>,
+    blend_mode: BlendMode,
+}
+
+impl SoftwareBackend {
+    pub fn new(width: u32, height: u32) -> SoftwareBackend {
+        SoftwareBackend {
+            pixels: RgbaImage::new(width, height),
+            images: HashMap::new(),
+            blend_mode: BlendMode::Normal,
+        }
+    }
+
+    // draw_sprite で name として使える画像を加える
+    pub fn insert_image(&mut self, name: &str, image: RgbaImage) {
+        self.images.insert(name.to_string(), image);
+    }
+
+    // 素材の一覧の画像をすべて読み込む
+    // パスは root からの相対パス。必須でない画像は読めなくても飛ばす（ブラウザで読み込みに失敗したときと同じ）
+    pub fn load_images(&mut self, manifest: &AssetManifest, root: &Path) -> Result<()> {
+        for asset in &manifest.images {
+            let path = root.join(&asset.path);
+            match image::open(&path) {
+                Ok(image) => self.insert_image(&asset.name, image.to_rgba8()),
+                Err(err) if asset.required => {
+                    return Err(err).with_context(|| format!("failed to load {}", path.display()));
+                }
+                Err(_) => {}
+            }
+        }
+        Ok(())
+    }
+
+    pub fn image(&self) -> &RgbaImage {
+        &self.pixels
+    }
+
+    pub fn into_image(self) -> RgbaImage {
+        self.pixels
+    }
+
+    pub fn save(&self, path: &Path) -> Result<()> {
+        self.pixels.save(path).with_context(|| format!("failed to write {}", path.display()))
+    }
+
+    // 画面の中に入る範囲に縮める
+    fn clip_x(&self, start: i64, end: i64) -> (i64, i64) {
+        (start.max(0), end.min(self.pixels.width() as i64))
+    }
+
+    fn clip_y(&self, start: i64, end: i64) -> (i64, i64) {
+        (start.max(0), end.min(self.pixels.height() as i64))
+    }
+
+    // 中心が (left..right, top..bottom) に入るピクセルを塗る
+    fn fill_area(&mut self, left: f64, top: f64, right: f64, bottom: f64, color: Color) {
+        let (x0, x1) = self.clip_x(pixel_start(left), pixel_start(right));
+        let (y0, y1) = self.clip_y(pixel_start(top), pixel_start(bottom));
+        for y in y0..y1 {
+            for x in x0..x1 {
+                blend(&mut self.pixels, self.blend_mode, x, y, [color.r, color.g, color.b], color.a);
+            }
+        }
+    }
+}
+
+// (x, y) のピクセルに color を alpha の強さで重ねる（画面の外なら何もしない）
+fn blend(pixels: &mut RgbaImage, mode: BlendMode, x: i64, y: i64, color: [u8; 3], alpha: f32) {
+    if x < 0 || y < 0 || x >= pixels.width() as i64 || y >= pixels.height() as i64 || alpha <= 0.0 {
+        return;
+    }
+    let alpha = alpha.min(1.0);
+    let pixel = pixels.get_pixel_mut(x as u32, y as u32);
+    let Rgba([r, g, b, a]) = *pixel;
+    let destination = [r, g, b];
+    let mut out = [0; 4];
+    match mode {
+        BlendMode::Normal => {
+            for i in 0..3 {
+                out[i] = (color[i] as f32 * alpha + destination[i] as f32 * (1.0 - alpha)).round() as u8;
+            }
+            out[3] = (alpha * 255.0 + a as f32 * (1.0 - alpha)).round() as u8;
+        }
+        BlendMode::Additive => {
+            for i in 0..3 {
+                out[i] = (destination[i] as f32 + color[i] as f32 * alpha).min(255.0).round() as u8;
+            }
+            out[3] = a.max((alpha * 255.0).round() as u8);
+        }
+    }
+    *pixel = Rgba(out);
+}
+
+// 中心が x 以上になる最初のピクセル
+fn pixel_start(x: f64) -> i64 {
+    (x - 0.5).ceil() as i64
+}
+
+impl RenderBackend for SoftwareBackend {
+    fn width(&self) -> f64 {
+        self.pixels.width() as f64
+    }
+
+    fn height(&self) -> f64 {
+        self.pixels.height() as f64
+    }
+
+    fn clear(&mut self, color: Color) {
+        let pixel = Rgba([color.r, color.g, color.b, (color.a * 255.0).round() as u8]);
+        for p in self.pixels.pixels_mut() {
+            *p = pixel;
+        }
+    }
+
+    fn draw_sprite(&mut self, image: &str, source: Rect, dest: Dest, flip_y: bool) -> bool {
+        let Some(image) = self.images.get(image) else {
+            return false;
+        };
+        if dest.width > 0.0 && dest.height > 0.0 && source.width > 0 && source.height > 0 {
+            let (x0, x1) = self.clip_x(pixel_start(dest.x), pixel_start(dest.x + dest.width));
+            let (y0, y1) = self.clip_y(pixel_start(dest.y), pixel_start(dest.y + dest.height));
+            for y in y0..y1 {
+                // 描画先のピクセルの中心に対応する元の画像のピクセル
+                let mut v = ((y as f64 + 0.5 - dest.y) / dest.height * source.height as f64) as u32;
+                v = v.min(source.height - 1);
+                if flip_y {
+                    v = source.height - 1 - v;
+                }
+                for x in x0..x1 {
+                    let u = (((x as f64 + 0.5 - dest.x) / dest.width * source.width as f64) as u32).min(source.width - 1);
+                    let (sx, sy) = (source.x + u, source.y + v);
+                    if sx >= image.width() || sy >= image.height() {
+                        continue;
+                    }
+                    let Rgba([r, g, b, a]) = *image.get_pixel(sx, sy);
+                    blend(&mut self.pixels, self.blend_mode, x, y, [r, g, b], a as f32 / 255.0);
+                }
+            }
+        }
+        true
+    }
+
+    fn draw_rect(&mut self, x: f64, y: f64, width: f64, height: f64, paint: Paint) {
+        match paint {
+            Paint::Fill(color) => self.fill_area(x, y, x + width, y + height, color),
+            Paint::Stroke(color, line_width) => {
+                // 辺を中心に線の太さの半分ずつ内外に広げた4本の帯（角は重ならないように分ける）
+                let half = line_width / 2.0;
+                self.fill_area(x - half, y - half, x + width + half, y + half, color);
+                self.fill_area(x - half, y + height - half, x + width + half, y + height + half, color);
+                self.fill_area(x - half, y + half, x + half, y + height - half, color);
+                self.fill_area(x + width - half, y + half, x + width + half, y + height - half, color);
+            }
+        }
+    }
+
+    fn draw_circle(&mut self, x: f64, y: f64, radius: f64, paint: Paint) {
+        let (color, inner, outer) = match paint {
+            Paint::Fill(color) => (color, f64::NEG_INFINITY, radius),
+            Paint::Stroke(color, line_width) => (color, radius - line_width / 2.0, radius + line_width / 2.0),
+        };
+        let (x0, x1) = (pixel_start(x - outer), pixel_start(x + outer));
+        let (y0, y1) = (pixel_start(y - outer), pixel_start(y + outer));
+        for py in y0..=y1 {
+            for px in x0..=x1 {
+                let distance = ((px as f64 + 0.5 - x).powi(2) + (py as f64 + 0.5 - y).powi(2)).sqrt();
+                if distance <= outer && distance >= inner {
+                    blend(&mut self.pixels, self.blend_mode, px, py, [color.r, color.g, color.b], color.a);
+                }
+            }
+        }
+    }
+
+    fn draw_line(&mut self, from: (f64, f64), to: (f64, f64), color: Color, width: f64) {
+        // 線分からの距離が太さの半分以内のピクセルを塗る
+        let half = width / 2.0;
+        let (dx, dy) = (to.0 - from.0, to.1 - from.1);
+        let length_squared = (dx * dx + dy * dy).max(f64::EPSILON);
+        let (x0, x1) = (pixel_start(from.0.min(to.0) - half), pixel_start(from.0.max(to.0) + half));
+        let (y0, y1) = (pixel_start(from.1.min(to.1) - half), pixel_start(from.1.max(to.1) + half));
+        for py in y0..=y1 {
+            for px in x0..=x1 {
+                let (cx, cy) = (px as f64 + 0.5, py as f64 + 0.5);
+                let t = (((cx - from.0) * dx + (cy - from.1) * dy) / length_squared).clamp(0.0, 1.0);
+                let distance = ((cx - from.0 - dx * t).powi(2) + (cy - from.1 - dy * t).powi(2)).sqrt();
+                if distance <= half {
+                    blend(&mut self.pixels, self.blend_mode, px, py, [color.r, color.g, color.b], color.a);
+                }
+            }
+        }
+    }
+
+    fn draw_text(&mut self, text: &str, x: f64, y: f64, size: u32, color: Color, align: TextAlign) {
+        // 文字の高さ（7ドット）が size のおよそ 3/4 になる倍率
+        let scale = ((size as f64 * 0.75 / bitmap_font::GLYPH_HEIGHT as f64).round() as u32).max(1);
+        let left = match align {
+            TextAlign::Left => x,
+            TextAlign::Center => x - bitmap_font::text_width(text, scale) as f64 / 2.0,
+        };
+        let top = pixel_start(y) - (bitmap_font::GLYPH_HEIGHT * scale) as i64;
+        let left = pixel_start(left);
+
+        for (i, c) in text.chars().enumerate() {
+            let glyph_left = left + (i as u32 * bitmap_font::ADVANCE * scale) as i64;
+            for (row, bits) in bitmap_font::glyph(c).iter().enumerate() {
+                for column in 0..bitmap_font::GLYPH_WIDTH {
+                    if bits & (1 << (bitmap_font::GLYPH_WIDTH - 1 - column)) == 0 {
+                        continue;
+                    }
+                    for dy in 0..scale {
+                        for dx in 0..scale {
+                            blend(
+                                &mut self.pixels,
+                                self.blend_mode,
+                                glyph_left + (column * scale + dx) as i64,
+                                top + (row as u32 * scale + dy) as i64,
+                                [color.r, color.g, color.b],
+                                color.a,
+                            );
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    fn set_blend_mode(&mut self, mode: BlendMode) {
+        self.blend_mode = mode;
+    }
+}
@@ -0,0 +1,177 @@
+use crate::collision::Hitbox;
+use crate::position::Position;
+
+// 衝突判定の候補を絞り込むための一様グリッド
+// 毎ステップ clear して入れ直し、近くにあるものだけを詳しく判定する
+// T はグリッドに入れる要素を指すもの（添字やプールのハンドル）
+//
+// 今使っているのは弾と敵、プレイヤーと敵の判定だけ（Simulation::check_collisions）
+// 敵の弾とプレイヤーは相手が1つなので総当たりのほうが速い。アイテムはまだゲームにないので、
+// 取る判定はアイテムを追加するときに、アイテムをこのグリッドに入れてプレイヤーで query する形で作る
+pub struct SpatialGrid<T = usize> {
+    cell_size: f32,
+    columns: usize,
+    rows: usize,
+    cells: Vec<Vec<T>>, // セルごとに入っている要素
+}
+
+impl<T: Copy + Ord> SpatialGrid<T> {
+    // width x height の範囲を cell_size ごとに区切る
+    // 範囲外にあるものは一番端のセルに入れるので、見落としはしない
+    pub fn new(width: f32, height: f32, cell_size: f32) -> SpatialGrid<T> {
+        let columns = ((width / cell_size).ceil() as usize).max(1);
+        let rows = ((height / cell_size).ceil() as usize).max(1);
+        SpatialGrid {
+            cell_size,
+            columns,
+            rows,
+            cells: vec![Vec::new(); columns * rows],
+        }
+    }
+
+    // 中身だけを空にする（確保したメモリは使い回す）
+    pub fn clear(&mut self) {
+        for cell in &mut self.cells {
+            cell.clear();
+        }
+    }
+
+    // 要素 key を、当たり判定が重なるすべてのセルに入れる
+    pub fn insert(&mut self, key: T, position: Position, hitbox: &Hitbox) {
+        let (left, top, right, bottom) = self.cell_range(position, hitbox);
+        for row in top..=bottom {
+            for column in left..=right {
+                self.cells[row * self.columns + column].push(key);
+            }
+        }
+    }
+
+    // 当たり判定が重なるセルに入っている要素を out に入れる（昇順、重複なし）
+    pub fn query(&self, position: Position, hitbox: &Hitbox, out: &mut Vec<T>) {
+        out.clear();
+        let (left, top, right, bottom) = self.cell_range(position, hitbox);
+        for row in top..=bottom {
+            for column in left..=right {
+                out.extend_from_slice(&self.cells[row * self.columns + column]);
+            }
+        }
+        // 複数のセルにまたがるときだけ重複を取り除く（1つのセルの中身は昇順に入っている）
+        if left != right || top != bottom {
+            out.sort_unstable();
+            out.dedup();
+        }
+    }
+
+    pub fn columns(&self) -> usize {
+        self.columns
+    }
+
+    pub fn rows(&self) -> usize {
+        self.rows
+    }
+
+    pub fn cell_size(&self) -> f32 {
+        self.cell_size
+    }
+
+    // 各セルに入っている要素の数（デバッグ表示用）
+    pub fn cell_counts(&self) -> impl Iterator<Item = (usize, usize, usize)> + '_ {
+        self.cells
+            .iter()
+            .enumerate()
+            .map(move |(i, cell)| (i % self.columns, i / self.columns, cell.len()))
+    }
+
+    // 当たり判定を囲む長方形が重なるセルの範囲（左, 上, 右, 下）
+    fn cell_range(&self, position: Position, hitbox: &Hitbox) -> (usize, usize, usize, usize) {
+        let center = hitbox.center(position);
+        let (half_width, half_height) = hitbox.half_extents();
+        (
+            self.column(center.x - half_width),
+            self.row(center.y - half_height),
+            self.column(center.x + half_width),
+            self.row(center.y + half_height),
+        )
+    }
+
+    fn column(&self, x: f32) -> usize {
+        ((x / self.cell_size).floor().max(0.0) as usize).min(self.columns - 1)
+    }
+
+    fn row(&self, y: f32) -> usize {
+        ((y / self.cell_size).floor().max(0.0) as usize).min(self.rows - 1)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rng::Rng;
+
+    const WIDTH: f32 = 800.0;
+    const HEIGHT: f32 = 600.0;
+
+    fn random_hitbox(rng: &mut Rng) -> Hitbox {
+        let hitbox = match rng.next_u32() % 3 {
+            0 => Hitbox::circle(rng.range(1.0, 120.0)),
+            1 => Hitbox::rect(rng.range(1.0, 200.0), rng.range(1.0, 200.0)),
+            _ => Hitbox::capsule(rng.range(1.0, 40.0), rng.range(0.0, 80.0)),
+        };
+        hitbox.with_offset(rng.range(-20.0, 20.0), rng.range(-20.0, 20.0))
+    }
+
+    // 画面の少し外まで散らばるように置く（範囲外は端のセルに入る）
+    fn scatter(rng: &mut Rng, count: usize) -> Vec<(Position, Hitbox)> {
+        (0..count)
+            .map(|_| {
+                let position = Position::new(rng.range(-100.0, WIDTH + 100.0), rng.range(-100.0, HEIGHT + 100.0));
+                (position, random_hitbox(rng))
+            })
+            .collect()
+    }
+
+    #[test]
+    fn query_finds_the_same_pairs_as_brute_force() {
+        let mut rng = Rng::new(10);
+        let mut candidates = Vec::new();
+        for cell_size in [16.0, 64.0, 128.0, 1000.0] {
+            for _ in 0..20 {
+                let targets = scatter(&mut rng, 60);
+                let queries = scatter(&mut rng, 60);
+
+                let mut grid = SpatialGrid::new(WIDTH, HEIGHT, cell_size);
+                for (i, (position, hitbox)) in targets.iter().enumerate() {
+                    grid.insert(i, *position, hitbox);
+                }
+
+                for (position, hitbox) in &queries {
+                    grid.query(*position, hitbox, &mut candidates);
+                    assert!(candidates.windows(2).all(|pair| pair[0] < pair[1]), "candidates must be sorted and unique");
+
+                    let found: Vec<usize> = candidates
+                        .iter()
+                        .copied()
+                        .filter(|&i| hitbox.intersects(*position, &targets[i].1, targets[i].0))
+                        .collect();
+                    let expected: Vec<usize> = (0..targets.len())
+                        .filter(|&i| hitbox.intersects(*position, &targets[i].1, targets[i].0))
+                        .collect();
+                    assert_eq!(found, expected, "cell size {}", cell_size);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn clear_empties_every_cell() {
+        let mut grid = SpatialGrid::new(WIDTH, HEIGHT, 100.0);
+        grid.insert(0, Position::new(400.0, 300.0), &Hitbox::circle(250.0));
+        assert!(grid.cell_counts().any(|(_, _, count)| count > 0));
+
+        grid.clear();
+        assert!(grid.cell_counts().all(|(_, _, count)| count == 0));
+        let mut candidates = vec![7];
+        grid.query(Position::new(400.0, 300.0), &Hitbox::circle(1000.0), &mut candidates);
+        assert!(candidates.is_empty());
+    }
+}
@@ -0,0 +1,176 @@
+use std::collections::BTreeMap;
+use anyhow::{anyhow, Context, Result};
+use serde::{Deserialize, Serialize};
+use crate::animation::{Animation, AnimationState};
+use crate::asset_manifest::{AssetManifest, ImageAsset};
+
+// 画像の中の長方形（ピクセル単位）
+#[derive(Clone, Copy, PartialEq, Debug, Serialize, Deserialize)]
+pub struct Rect {
+    pub x: u32,
+    pub y: u32,
+    pub width: u32,
+    pub height: u32,
+}
+
+// スプライト1つ分のコマの切り出し位置
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct AtlasSprite {
+    pub image: String, // コマが入っている画像の名前
+    pub frames: Vec<Rect>,
+    pub anchor: [f32; 2], // 描画位置に合わせる点（コマの大きさに対する割合）
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub animations: BTreeMap<String, Animation>,
+}
+
+impl AtlasSprite {
+    // 再生中のアニメーションに合わせたコマ（アニメーションがなければ最初のコマ）
+    // コマが1つもなければ None
+    pub fn frame(&self, animation: Option<&AnimationState>) -> Option<Rect> {
+        let index = animation
+            .and_then(|state| {
+                self.animations
+                    .get(state.clip())
+                    .map(|clip| clip.frame_at(state.time()))
+            })
+            .unwrap_or(0);
+        self.frames.get(index as usize).or(self.frames.first()).copied()
+    }
+}
+
+// スプライト名からコマの切り出し位置を引く表
+#[derive(Clone, Debug, Default)]
+pub struct SpriteAtlas {
+    sprites: BTreeMap<String, AtlasSprite>,
+}
+
+impl SpriteAtlas {
+    // 素材の一覧の画像をそれぞれスプライトシートとして切り分ける
+    pub fn from_manifest(manifest: &AssetManifest) -> SpriteAtlas {
+        let sprites = manifest
+            .images
+            .iter()
+            .map(|image| (image.name.clone(), sheet_sprite(image)))
+            .collect();
+        SpriteAtlas { sprites }
+    }
+
+    pub fn sprite(&self, name: &str) -> Option<&AtlasSprite> {
+        self.sprites.get(name)
+    }
+
+    // スプライト name のアニメーション clip の1周の長さ（秒単位）
+    pub fn clip_duration(&self, name: &str, clip: &str) -> Option<f32> {
+        self.sprite(name)?.animations.get(clip).map(|animation| animation.total_duration())
+    }
+}
+
+fn sheet_sprite(image: &ImageAsset) -> AtlasSprite {
+    let frame = image.frame_size();
+    let columns = image.width / frame.width;
+    let frames = (0..image.frame_count())
+        .map(|i| Rect {
+            x: (i % columns) * frame.width,
+            y: (i / columns) * frame.height,
+            width: frame.width,
+            height: frame.height,
+        })
+        .collect();
+    AtlasSprite {
+        image: image.name.clone(),
+        frames,
+        anchor: image.anchor,
+        animations: image.animations.clone(),
+    }
+}
+
+// アトラス作成ツール（src/bin/pack_atlas.rs）が書き出す、すべてのコマを1枚にまとめた画像の説明
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct PackedAtlas {
+    pub image: String, // まとめた画像のパス
+    pub width: u32,
+    pub height: u32,
+    pub sprites: BTreeMap<String, AtlasSprite>,
+}
+
+impl PackedAtlas {
+    // 読み込むときに使う画像の名前
+    pub const IMAGE_NAME: &'static str = "atlas";
+
+    pub fn from_json(json: &str) -> Result<PackedAtlas> {
+        let atlas: PackedAtlas = serde_json::from_str(json).context("failed to parse atlas")?;
+        atlas.validate()?;
+        Ok(atlas)
+    }
+
+    fn validate(&self) -> Result<()> {
+        for (name, sprite) in &self.sprites {
+            if sprite.frames.is_empty() {
+                return Err(anyhow!("sprite \"{}\": no frames", name));
+            }
+            if let Some(rect) = sprite
+                .frames
+                .iter()
+                .find(|rect| rect.x + rect.width > self.width || rect.y + rect.height > self.height)
+            {
+                return Err(anyhow!("sprite \"{}\": frame {:?} is outside the {}x{} atlas", name, rect, self.width, self.height));
+            }
+            for (clip, animation) in &sprite.animations {
+                animation
+                    .validate(sprite.frames.len() as u32)
+                    .with_context(|| format!("sprite \"{}\": animation \"{}\"", name, clip))?;
+            }
+        }
+        Ok(())
+    }
+
+    // 読み込む素材の一覧（まとめた画像1枚だけ）と、描画に使う表に分ける
+    pub fn into_parts(self) -> (AssetManifest, SpriteAtlas) {
+        let manifest = AssetManifest {
+            images: vec![ImageAsset {
+                name: PackedAtlas::IMAGE_NAME.to_string(),
+                path: self.image,
+                width: self.width,
+                height: self.height,
+                frame: None,
+                anchor: [0.0, 0.0],
+                hitbox: None,
+                animations: BTreeMap::new(),
+                required: true,
+            }],
+        };
+        (manifest, SpriteAtlas { sprites: self.sprites })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::animation::LoopMode;
+
+    fn sprite(frame_count: u32) -> AtlasSprite {
+        AtlasSprite {
+            image: "sheet".to_string(),
+            frames: (0..frame_count).map(|i| Rect { x: i * 10, y: 0, width: 10, height: 10 }).collect(),
+            anchor: [0.5, 0.5],
+            animations: BTreeMap::new(),
+        }
+    }
+
+    #[test]
+    fn frame_falls_back_to_the_first_frame() {
+        let mut sprite = sprite(3);
+        sprite.animations.insert(
+            "walk".to_string(),
+            Animation { frames: vec![2, 1], frame_time: 0.1, durations: None, mode: LoopMode::Loop },
+        );
+        assert_eq!(sprite.frame(None).map(|rect| rect.x), Some(0));
+        assert_eq!(sprite.frame(Some(&AnimationState::new("walk"))).map(|rect| rect.x), Some(20));
+        assert_eq!(sprite.frame(Some(&AnimationState::new("missing"))).map(|rect| rect.x), Some(0));
+    }
+
+    #[test]
+    fn frame_is_none_without_frames() {
+        assert_eq!(sprite(0).frame(None), None);
+    }
+}
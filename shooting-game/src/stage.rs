@@ -0,0 +1,312 @@
+use std::collections::HashMap;
+use anyhow::{anyhow, Context, Result};
+use serde::Deserialize;
+use crate::bullet_pattern::{BulletPattern, PatternLibrary};
+use crate::enemy_type::EnemyType;
+use crate::position::Position;
+use crate::rng::Rng;
+use crate::background::BackgroundConfig;
+
+// 組み込みのステージ
+const DEFAULT_STAGE: &str = include_str!("../stages/stage1.json");
+
+// ステージのスクリプト
+// 上から順にウェーブを実行し、最後のウェーブが終わって敵がいなくなったらステージクリア
+#[derive(Clone, Deserialize)]
+pub struct StageScript {
+    pub name: String,
+    pub waves: Vec<Wave>,
+    #[serde(default)]
+    pub patterns: HashMap<String, BulletPattern>, // このステージで使う撃ち方（組み込みのものと同じ名前なら上書き）
+    #[serde(default)]
+    pub background: BackgroundConfig, // なければ組み込みの背景
+}
+
+// 同じ種類の敵をまとめて出現させる単位
+#[derive(Clone, Deserialize)]
+pub struct Wave {
+    pub enemy_type: EnemyType,
+    pub count: u32,
+    #[serde(default)]
+    pub delay: f64, // 前のウェーブが終わってから最初の敵が出るまでの時間（秒単位）
+    #[serde(default)]
+    pub interval: f64, // 敵を1体ずつ出す間隔（秒単位）
+    #[serde(default)]
+    pub formation: Formation,
+    #[serde(default)]
+    pub wait_until_cleared: bool, // true なら画面上の敵がいなくなるまで次のウェーブに進まない
+    #[serde(default)]
+    pub pattern: Option<String>, // 敵が撃つ弾の撃ち方の名前（なければ撃たない）
+    #[serde(default = "default_fire_interval")]
+    pub fire_interval: f32, // 弾の発射間隔（秒単位）
+}
+
+fn default_fire_interval() -> f32 {
+    1.5
+}
+
+// 敵の出現位置の並び方
+// x は画面の幅に対する割合（0.0が左端、1.0が右端）
+#[derive(Clone, Deserialize, Default)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Formation {
+    // ランダムな位置
+    #[default]
+    Random,
+    // 全員同じ位置から縦一列に出る
+    Column { x: f32 },
+    // from から to まで等間隔に横一列に並ぶ
+    Line { from: f32, to: f32 },
+    // x を先頭にV字に並ぶ（spacing は隣との間隔）
+    V { x: f32, spacing: f32 },
+}
+
+impl Formation {
+    // count 体中 index 番目の敵の出現位置
+    pub fn position(&self, index: u32, count: u32, width: f32, rng: &mut Rng) -> Position {
+        match *self {
+            Formation::Random => Position::new(rng.range(0.0, width), 0.0),
+            Formation::Column { x } => Position::new(x * width, 0.0),
+            Formation::Line { from, to } => {
+                let t = if count > 1 { index as f32 / (count - 1) as f32 } else { 0.5 };
+                Position::new((from + (to - from) * t) * width, 0.0)
+            }
+            Formation::V { x, spacing } => {
+                // 0番目が先頭、以降は左右交互に後ろへ下がる
+                let rank = index.div_ceil(2) as f32;
+                let side = if index % 2 == 1 { -1.0 } else { 1.0 };
+                Position::new((x + side * rank * spacing) * width, -rank * spacing * width)
+            }
+        }
+    }
+}
+
+impl StageScript {
+    pub fn from_json(json: &str) -> Result<StageScript> {
+        let script: StageScript = serde_json::from_str(json).context("failed to parse stage script")?;
+        script.validate()?;
+        Ok(script)
+    }
+
+    // ウェーブが参照している撃ち方が存在するか確認する
+    fn validate(&self) -> Result<()> {
+        let library = self.pattern_library();
+        for (i, wave) in self.waves.iter().enumerate() {
+            if let Some(name) = &wave.pattern {
+                if library.get(name).is_none() {
+                    return Err(anyhow!("wave {}: unknown bullet pattern \"{}\"", i, name));
+                }
+            }
+            if wave.fire_interval <= 0.0 {
+                return Err(anyhow!("wave {}: fire_interval must be positive", i));
+            }
+        }
+        self.background.validate()?;
+        Ok(())
+    }
+
+    // 組み込みの撃ち方にこのステージの撃ち方を加えた一覧
+    pub fn pattern_library(&self) -> PatternLibrary {
+        let mut library = PatternLibrary::default();
+        for (name, pattern) in &self.patterns {
+            library.insert(name, pattern.clone());
+        }
+        library
+    }
+
+    pub fn from_file(path: &str) -> Result<StageScript> {
+        let json = std::fs::read_to_string(path).with_context(|| format!("failed to read {}", path))?;
+        StageScript::from_json(&json)
+    }
+}
+
+impl Default for StageScript {
+    fn default() -> StageScript {
+        StageScript::from_json(DEFAULT_STAGE).expect("built-in stage should be valid")
+    }
+}
+
+// 出現させる敵
+pub struct SpawnRequest {
+    pub enemy_type: EnemyType,
+    pub position: Position,
+    pub pattern: Option<String>,
+    pub fire_interval: f32,
+}
+
+// ステージスクリプトの進行状況
+pub struct StageRunner {
+    script: StageScript,
+    wave_index: usize,
+    spawned: u32, // 現在のウェーブで出現させた数
+    timer: f64,   // 現在のウェーブが始まってからの時間（秒単位）
+}
+
+impl StageRunner {
+    pub fn new(script: StageScript) -> StageRunner {
+        StageRunner {
+            script,
+            wave_index: 0,
+            spawned: 0,
+            timer: 0.0,
+        }
+    }
+
+    // 時間を進めて、このステップで出現させる敵を返す
+    // field_cleared は画面上に敵が残っていないかどうか
+    pub fn update(&mut self, dt: f64, field_cleared: bool, width: f32, rng: &mut Rng) -> Vec<SpawnRequest> {
+        let mut requests = Vec::new();
+        let mut field_cleared = field_cleared;
+
+        self.timer += dt;
+
+        while let Some(wave) = self.script.waves.get(self.wave_index) {
+            while self.spawned < wave.count
+                && self.timer >= wave.delay + wave.interval * self.spawned as f64
+            {
+                requests.push(SpawnRequest {
+                    enemy_type: wave.enemy_type,
+                    position: wave.formation.position(self.spawned, wave.count, width, rng),
+                    pattern: wave.pattern.clone(),
+                    fire_interval: wave.fire_interval,
+                });
+                self.spawned += 1;
+                field_cleared = false;
+            }
+
+            if self.spawned < wave.count || (wave.wait_until_cleared && !field_cleared) {
+                break;
+            }
+
+            // 次のウェーブの待ち時間はこのウェーブが終わった時点から数える
+            self.timer = 0.0;
+            self.wave_index += 1;
+            self.spawned = 0;
+        }
+
+        requests
+    }
+
+    // 最初のウェーブからやり直す
+    pub fn reset(&mut self) {
+        self.wave_index = 0;
+        self.spawned = 0;
+        self.timer = 0.0;
+    }
+
+    // 全てのウェーブを出し終えたかどうか
+    pub fn is_finished(&self) -> bool {
+        self.wave_index >= self.script.waves.len()
+    }
+
+    // 現在のウェーブ番号（0始まり）
+    pub fn wave_index(&self) -> usize {
+        self.wave_index
+    }
+
+    // 現在のウェーブで出現させた数
+    pub fn spawned(&self) -> u32 {
+        self.spawned
+    }
+
+    // 現在のウェーブが始まってからの時間（秒単位）
+    pub fn timer(&self) -> f64 {
+        self.timer
+    }
+
+    pub fn script(&self) -> &StageScript {
+        &self.script
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DT: f64 = 0.1;
+
+    fn runner(json: &str) -> StageRunner {
+        StageRunner::new(StageScript::from_json(json).unwrap())
+    }
+
+    // field_cleared を渡しながら seconds 秒進め、出現した敵の種類をステップごとに返す
+    fn run(runner: &mut StageRunner, seconds: f64, field_cleared: bool) -> Vec<Vec<EnemyType>> {
+        let mut rng = Rng::new(1);
+        let steps = (seconds / DT).round() as usize;
+        (0..steps)
+            .map(|_| {
+                runner
+                    .update(DT, field_cleared, 800.0, &mut rng)
+                    .iter()
+                    .map(|request| request.enemy_type)
+                    .collect()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn waves_spawn_in_order_after_delay_and_interval() {
+        let mut runner = runner(r#"{ "name": "test", "waves": [
+            { "enemy_type": "Regular", "count": 2, "delay": 0.5, "interval": 1.0 },
+            { "enemy_type": "Fast", "count": 1, "delay": 0.5 }
+        ] }"#);
+
+        let spawned = run(&mut runner, 3.0, false);
+        let times: Vec<(usize, EnemyType)> = spawned
+            .iter()
+            .enumerate()
+            .flat_map(|(step, types)| types.iter().map(move |&t| (step, t)))
+            .collect();
+        // 0.5秒と1.5秒に Regular、2つ目のウェーブはそこから0.5秒後
+        assert_eq!(times, vec![(4, EnemyType::Regular), (14, EnemyType::Regular), (19, EnemyType::Fast)]);
+        assert!(runner.is_finished());
+    }
+
+    #[test]
+    fn wait_until_cleared_holds_the_next_wave() {
+        let mut runner = runner(r#"{ "name": "test", "waves": [
+            { "enemy_type": "Regular", "count": 1, "wait_until_cleared": true },
+            { "enemy_type": "Strong", "count": 1 }
+        ] }"#);
+
+        let spawned = run(&mut runner, 2.0, false);
+        assert_eq!(spawned.concat(), vec![EnemyType::Regular]);
+        assert_eq!(runner.wave_index(), 0);
+
+        let spawned = run(&mut runner, 0.1, true);
+        assert_eq!(spawned.concat(), vec![EnemyType::Strong]);
+        assert!(runner.is_finished());
+    }
+
+    #[test]
+    fn formations_place_enemies_across_the_width() {
+        let mut rng = Rng::new(1);
+        let line = Formation::Line { from: 0.25, to: 0.75 };
+        let xs: Vec<f32> = (0..3).map(|i| line.position(i, 3, 800.0, &mut rng).x).collect();
+        assert_eq!(xs, vec![200.0, 400.0, 600.0]);
+
+        // 先頭のあとは左右交互に1段ずつ後ろへ下がる
+        let v = Formation::V { x: 0.5, spacing: 0.1 };
+        let expected = [(400.0, 0.0), (320.0, -80.0), (480.0, -80.0), (240.0, -160.0)];
+        for (i, (x, y)) in expected.into_iter().enumerate() {
+            let position = v.position(i as u32, 4, 800.0, &mut rng);
+            assert!((position.x - x).abs() < 1e-3 && (position.y - y).abs() < 1e-3, "{}: {:?}", i, position);
+        }
+    }
+
+    #[test]
+    fn unknown_pattern_is_rejected() {
+        let result = StageScript::from_json(r#"{ "name": "test", "waves": [
+            { "enemy_type": "Regular", "count": 1, "pattern": "no_such_pattern" }
+        ] }"#);
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn from_file_reads_a_stage_script() {
+        let path = concat!(env!("CARGO_MANIFEST_DIR"), "/stages/stage1.json");
+        let script = StageScript::from_file(path).unwrap();
+        assert_eq!(script.waves.len(), StageScript::default().waves.len());
+        assert!(StageScript::from_file("stages/no_such_stage.json").is_err());
+    }
+}
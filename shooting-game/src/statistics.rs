@@ -0,0 +1,86 @@
+use crate::event::{DespawnReason, EntityKind, GameEvent};
+
+// GameEvent から集計したプレイの記録
+#[derive(Clone, Copy, Default, Debug)]
+pub struct Statistics {
+    pub enemies_destroyed: u32, // 倒した敵の数
+    pub enemies_escaped: u32,   // 逃がした敵の数
+    pub bullets_hit: u32,       // 敵に当たった自機の弾の数
+    pub bullets_missed: u32,    // 何にも当たらずに画面外に出た自機の弾の数
+    pub hits_taken: u32,        // プレイヤーが当たった回数
+}
+
+impl Statistics {
+    pub fn record(&mut self, event: &GameEvent) {
+        match *event {
+            GameEvent::Despawned { kind, reason, .. } => match (kind, reason) {
+                (EntityKind::Enemy(_), DespawnReason::Destroyed) => self.enemies_destroyed += 1,
+                (EntityKind::PlayerBullet, DespawnReason::Collided) => self.bullets_hit += 1,
+                (EntityKind::PlayerBullet, DespawnReason::LeftPlayfield) => self.bullets_missed += 1,
+                _ => {}
+            },
+            GameEvent::EnemyEscaped { .. } => self.enemies_escaped += 1,
+            // 同じステップで複数の敵にぶつかってもライフは1つしか減らないので、減ったライフで数える
+            GameEvent::LifeLost { .. } => self.hits_taken += 1,
+            GameEvent::GameStarted { .. }
+            | GameEvent::Continued { .. }
+            | GameEvent::StateChanged { .. } => {}
+        }
+    }
+
+    // 自機の弾の命中率（0.0〜1.0）
+    pub fn accuracy(&self) -> f32 {
+        let total = self.bullets_hit + self.bullets_missed;
+        if total == 0 {
+            0.0
+        } else {
+            self.bullets_hit as f32 / total as f32
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::enemy_type::EnemyType;
+    use crate::position::Position;
+
+    fn despawned(kind: EntityKind, reason: DespawnReason) -> GameEvent {
+        GameEvent::Despawned { kind, reason, position: Position::new(0.0, 0.0) }
+    }
+
+    #[test]
+    fn record_counts_each_kind_of_event() {
+        let mut statistics = Statistics::default();
+        for event in [
+            despawned(EntityKind::Enemy(EnemyType::Regular), DespawnReason::Destroyed),
+            despawned(EntityKind::Enemy(EnemyType::Fast), DespawnReason::Destroyed),
+            despawned(EntityKind::Enemy(EnemyType::Regular), DespawnReason::Collided),
+            despawned(EntityKind::Enemy(EnemyType::Strong), DespawnReason::LeftPlayfield),
+            GameEvent::EnemyEscaped { enemy_type: EnemyType::Strong, penalty: 0 },
+            despawned(EntityKind::PlayerBullet, DespawnReason::Collided),
+            despawned(EntityKind::PlayerBullet, DespawnReason::LeftPlayfield),
+            despawned(EntityKind::EnemyBullet, DespawnReason::Collided),
+            despawned(EntityKind::EnemyBullet, DespawnReason::LeftPlayfield),
+            GameEvent::LifeLost { life: 2 },
+            GameEvent::LifeLost { life: 1 },
+        ] {
+            statistics.record(&event);
+        }
+
+        assert_eq!(statistics.enemies_destroyed, 2);
+        assert_eq!(statistics.enemies_escaped, 1);
+        assert_eq!(statistics.bullets_hit, 1);
+        assert_eq!(statistics.bullets_missed, 1);
+        assert_eq!(statistics.hits_taken, 2);
+    }
+
+    #[test]
+    fn accuracy_is_the_share_of_bullets_that_hit() {
+        let mut statistics = Statistics::default();
+        assert_eq!(statistics.accuracy(), 0.0);
+        statistics.bullets_hit = 3;
+        statistics.bullets_missed = 1;
+        assert_eq!(statistics.accuracy(), 0.75);
+    }
+}
@@ -0,0 +1,95 @@
+pub const DEFAULT_TICK_RATE: f64 = 60.0; // 1秒あたりの更新回数
+pub const DEFAULT_MAX_STEPS: u32 = 5; // 1フレームで追いつく最大ステップ数
+
+// 固定タイムステップのアキュムレータ
+// 描画フレームの経過時間を貯めて、決まった刻み幅の更新を何回行うかを決める
+pub struct FixedTimestep {
+    step: f64, // 1ステップの長さ（秒単位）
+    max_steps: u32,
+    accumulator: f64,
+}
+
+impl FixedTimestep {
+    pub fn new(tick_rate: f64, max_steps: u32) -> FixedTimestep {
+        FixedTimestep {
+            step: 1.0 / tick_rate,
+            max_steps,
+            accumulator: 0.0,
+        }
+    }
+
+    // 1ステップの長さ（秒単位）
+    pub fn dt(&self) -> f64 {
+        self.step
+    }
+
+    // 経過時間（秒単位）を加算し、このフレームで実行するステップ数を返す
+    pub fn advance(&mut self, elapsed: f64) -> u32 {
+        self.accumulator += elapsed.max(0.0);
+
+        let mut steps = 0;
+        while self.accumulator >= self.step && steps < self.max_steps {
+            self.accumulator -= self.step;
+            steps += 1;
+        }
+
+        // 追いつけなかった分は捨てて、処理落ちが連鎖しないようにする
+        if self.accumulator >= self.step {
+            self.accumulator %= self.step;
+        }
+
+        steps
+    }
+
+    // 前のステップから次のステップまでの補間係数（0.0〜1.0）
+    pub fn alpha(&self) -> f64 {
+        self.accumulator / self.step
+    }
+}
+
+impl Default for FixedTimestep {
+    fn default() -> FixedTimestep {
+        FixedTimestep::new(DEFAULT_TICK_RATE, DEFAULT_MAX_STEPS)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rng::Rng;
+
+    #[test]
+    fn advance_runs_one_step_per_elapsed_tick() {
+        // 刻み幅を 0.25 秒にして割り切れる値で確かめる
+        let mut timestep = FixedTimestep::new(4.0, 5);
+        assert_eq!(timestep.advance(0.1), 0);
+        assert_eq!(timestep.advance(0.2), 1);
+        // 前の余り 0.05 秒と合わせて 0.65 秒なので、2ステップ進めて 0.15 秒残る
+        assert_eq!(timestep.advance(0.6), 2);
+        assert!((timestep.alpha() - 0.6).abs() < 1e-9, "{}", timestep.alpha());
+        // 時間が戻っても貯めた分は減らさない
+        assert_eq!(timestep.advance(-1.0), 0);
+        assert!((timestep.alpha() - 0.6).abs() < 1e-9);
+    }
+
+    #[test]
+    fn catch_up_is_capped_and_the_backlog_is_dropped() {
+        let mut timestep = FixedTimestep::new(4.0, 5);
+        assert_eq!(timestep.advance(10.125), 5);
+        assert!((timestep.alpha() - 0.5).abs() < 1e-9, "{}", timestep.alpha());
+        // 捨てた分をあとのフレームで取り戻そうとしない
+        assert_eq!(timestep.advance(0.0), 0);
+        assert_eq!(timestep.advance(0.125), 1);
+    }
+
+    #[test]
+    fn alpha_stays_below_one() {
+        let mut timestep = FixedTimestep::default();
+        let mut rng = Rng::new(2);
+        for _ in 0..10_000 {
+            timestep.advance(rng.range(0.0, 0.2) as f64);
+            let alpha = timestep.alpha();
+            assert!((0.0..1.0).contains(&alpha), "{}", alpha);
+        }
+    }
+}
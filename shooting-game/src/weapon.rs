@@ -0,0 +1,158 @@
+use crate::bullet::Bullet;
+use crate::position::Position;
+
+// 弾の撃ち方
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum ShotPattern {
+    Single, // 真上に1発
+    Twin,   // 左右に並べて2発
+    Spread, // 扇状に3発
+    Laser,  // 細くて速い弾を連射
+}
+
+impl ShotPattern {
+    // 次に撃てるまでの間隔（秒単位）
+    pub fn fire_interval(&self) -> f32 {
+        match self {
+            ShotPattern::Single => 0.2,
+            ShotPattern::Twin => 0.25,
+            ShotPattern::Spread => 0.35,
+            ShotPattern::Laser => 0.06,
+        }
+    }
+
+    // origin から発射する弾を out に加える
+    pub fn spawn(&self, origin: Position, out: &mut impl Extend<Bullet>) {
+        let speed = 300.0;
+        match self {
+            // 1発しか撃たないので、当てたときの火力が Twin や Spread と同じくらいになるよう2倍にする
+            ShotPattern::Single => out.extend([Bullet::new(origin.x, origin.y).with_damage(2)]),
+            ShotPattern::Twin => out.extend([
+                Bullet::new(origin.x - 30.0, origin.y),
+                Bullet::new(origin.x + 30.0, origin.y),
+            ]),
+            ShotPattern::Spread => out.extend([-15.0f32, 0.0, 15.0].iter().map(|angle| {
+                let angle = angle.to_radians();
+                Bullet::with_velocity(origin.x, origin.y, speed * angle.sin(), -speed * angle.cos())
+            })),
+            ShotPattern::Laser => out.extend([
+                Bullet::with_velocity(origin.x, origin.y - 45.0, 0.0, -900.0)
+                    .with_sprite("laser", 24.0, 90.0),
+            ]),
+        }
+    }
+}
+
+// プレイヤーの武器
+// 発射ボタンを押している間、撃ち方ごとの間隔で自動的に撃ち続ける
+pub struct Weapon {
+    pattern: ShotPattern,
+    cooldown: f32, // 次に撃てるまでの残り時間（秒単位）
+}
+
+impl Weapon {
+    pub fn new(pattern: ShotPattern) -> Weapon {
+        Weapon { pattern, cooldown: 0.0 }
+    }
+
+    pub fn get_pattern(&self) -> ShotPattern {
+        self.pattern
+    }
+
+    pub fn set_pattern(&mut self, pattern: ShotPattern) {
+        self.pattern = pattern;
+    }
+
+    // 時間を進め、撃てる状態で firing が true なら発射した弾を out に加える
+    pub fn update(&mut self, dt: f32, firing: bool, origin: Position, out: &mut impl Extend<Bullet>) {
+        self.cooldown = (self.cooldown - dt).max(0.0);
+
+        if !firing || self.cooldown > 0.0 {
+            return;
+        }
+
+        self.cooldown = self.pattern.fire_interval();
+        self.pattern.spawn(origin, out);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DT: f32 = 1.0 / 60.0;
+    const ORIGIN: Position = Position { x: 400.0, y: 500.0 };
+
+    // firing を渡しながら steps ステップ進め、撃った弾の数を返す
+    fn fire(weapon: &mut Weapon, steps: usize, firing: bool) -> usize {
+        let mut bullets = Vec::new();
+        for _ in 0..steps {
+            weapon.update(DT, firing, ORIGIN, &mut bullets);
+        }
+        bullets.len()
+    }
+
+    #[test]
+    fn holding_fire_shoots_at_the_pattern_interval() {
+        let mut weapon = Weapon::new(ShotPattern::Single);
+        // 押した瞬間に撃ち、間隔が過ぎるまでは撃たない
+        assert_eq!(fire(&mut weapon, 1, true), 1);
+        assert_eq!(fire(&mut weapon, 10, true), 0);
+        assert_eq!(fire(&mut weapon, 3, true), 1);
+
+        // 押し続けると、間隔（刻み幅の端数は次のステップに切り上がる）ごとに撃つ
+        for pattern in [ShotPattern::Single, ShotPattern::Twin, ShotPattern::Spread, ShotPattern::Laser] {
+            let mut weapon = Weapon::new(pattern);
+            let mut counted = Weapon::new(pattern);
+            let per_shot = fire(&mut counted, 1, true);
+            let shots = fire(&mut weapon, 120, true) / per_shot;
+            let interval = pattern.fire_interval();
+            let most = (2.0 / interval) as usize + 1;
+            let least = (2.0 / (interval + DT)) as usize;
+            assert!((least..=most).contains(&shots), "{:?}: {} shots", pattern, shots);
+        }
+    }
+
+    #[test]
+    fn cooldown_keeps_running_while_not_firing() {
+        let mut weapon = Weapon::new(ShotPattern::Spread);
+        assert_eq!(fire(&mut weapon, 1, true), 3);
+        assert_eq!(fire(&mut weapon, 30, false), 0);
+        // 離している間に間隔が過ぎたので、押し直すとすぐに撃てる
+        assert_eq!(fire(&mut weapon, 1, true), 3);
+        assert_eq!(fire(&mut weapon, 1, true), 0);
+    }
+
+    #[test]
+    fn patterns_spawn_their_bullets() {
+        let spawn = |pattern: ShotPattern| {
+            let mut bullets = Vec::new();
+            pattern.spawn(ORIGIN, &mut bullets);
+            bullets
+        };
+
+        let single = spawn(ShotPattern::Single);
+        assert_eq!(single.len(), 1);
+        assert_eq!(single[0].get_damage(), 2);
+
+        let twin: Vec<f32> = spawn(ShotPattern::Twin).iter().map(|bullet| bullet.get_position().x).collect();
+        assert_eq!(twin, [370.0, 430.0]);
+
+        // 扇状の弾は左右対称に広がり、どれも上へ進む
+        let mut spread = spawn(ShotPattern::Spread);
+        for bullet in &mut spread {
+            bullet.update(1.0);
+        }
+        let moved: Vec<(f32, f32)> = spread
+            .iter()
+            .map(|bullet| (bullet.get_position().x - ORIGIN.x, bullet.get_position().y - ORIGIN.y))
+            .collect();
+        assert_eq!(moved.len(), 3);
+        assert!((moved[0].0 + moved[2].0).abs() < 1e-3 && moved[0].0 < 0.0 && moved[1].0 == 0.0);
+        assert!(moved.iter().all(|(_, dy)| *dy < 0.0));
+        assert!(spread.iter().all(|bullet| bullet.get_damage() == 1));
+
+        let laser = spawn(ShotPattern::Laser);
+        assert_eq!(laser[0].get_sprite(), "laser");
+    }
+}
@@ -0,0 +1,213 @@
+// 描画結果のスナップショットテスト
+// シードを決めたゲームを自動操縦で進めて SoftwareBackend で描き、tests/golden/ の画像と比べる
+//
+//   cargo test --test snapshots
+//   UPDATE_GOLDENS=1 cargo test --test snapshots   # 意図して見た目を変えたときに正解の画像を作り直す
+//
+// 一致しなかったときは target/snapshots/ に描いた画像（*.actual.png）と差分の画像（*.diff.png）を書き出す
+use std::path::{Path, PathBuf};
+use image::{Rgba, RgbaImage};
+use shooting_game::asset_manifest::AssetManifest;
+use shooting_game::event::GameEvent;
+use shooting_game::input::Input;
+use shooting_game::renderer::Renderer;
+use shooting_game::simulation::Simulation;
+use shooting_game::software_backend::SoftwareBackend;
+use shooting_game::sprite_atlas::SpriteAtlas;
+use shooting_game::statistics::Statistics;
+
+const WIDTH: u32 = 800;
+const HEIGHT: u32 = 600;
+const SEED: u64 = 42;
+const DT: f64 = 1.0 / 60.0;
+
+// 色の違いをこれより小さければ同じ色とみなす（YIQ 色空間での差の最大値に対する割合）
+const COLOR_THRESHOLD: f64 = 0.1;
+// 違うピクセルがこの割合までなら一致とみなす
+const MAX_DIFF_RATIO: f64 = 0.001;
+
+#[test]
+fn title_screen() {
+    let game = TestGame::new();
+    game.check("title");
+}
+
+#[test]
+fn playing_with_explosion() {
+    let mut game = TestGame::new();
+    game.start();
+    game.run(240);
+    game.check("playing");
+}
+
+#[test]
+fn paused_overlay() {
+    let mut game = TestGame::new();
+    game.start();
+    game.run(630);
+    game.step(&Input { pause: true, ..Input::default() });
+    game.check("paused");
+}
+
+#[test]
+fn results_screen() {
+    let mut game = TestGame::new();
+    game.start();
+    game.run(2200);
+    game.check("results");
+}
+
+// シードを固定したゲームと、それを描く SoftwareBackend
+struct TestGame {
+    simulation: Simulation,
+    statistics: Statistics,
+    tick: u64,
+}
+
+impl TestGame {
+    fn new() -> TestGame {
+        TestGame {
+            simulation: Simulation::new(WIDTH as f32, HEIGHT as f32, SEED),
+            statistics: Statistics::default(),
+            tick: 0,
+        }
+    }
+
+    fn start(&mut self) {
+        self.simulation.start();
+    }
+
+    // 2秒ごとに左右を入れ替えながら撃ち続ける自動操縦で ticks ステップ進める
+    fn run(&mut self, ticks: u64) {
+        for _ in 0..ticks {
+            let left = (self.tick / 120).is_multiple_of(2);
+            self.step(&Input { left, right: !left, fire: true, ..Input::default() });
+        }
+    }
+
+    fn step(&mut self, input: &Input) {
+        self.tick += 1;
+        self.simulation.step(input, DT);
+        for event in self.simulation.drain_events() {
+            if let GameEvent::GameStarted { .. } = event {
+                self.statistics = Statistics::default();
+            }
+            self.statistics.record(&event);
+        }
+    }
+
+    fn render(&self) -> RgbaImage {
+        let root = Path::new(env!("CARGO_MANIFEST_DIR"));
+        let manifest = AssetManifest::default();
+        let mut backend = SoftwareBackend::new(WIDTH, HEIGHT);
+        backend.load_images(&manifest, root).expect("images in assets.json should load");
+        let mut renderer = Renderer::new(backend, SpriteAtlas::from_manifest(&manifest));
+        renderer.render(&self.simulation, &self.statistics, 1.0);
+        renderer.backend().image().clone()
+    }
+
+    // tests/golden/<name>.png と比べる
+    fn check(&self, name: &str) {
+        let actual = self.render();
+        let golden_path = golden_dir().join(format!("{}.png", name));
+
+        if std::env::var_os("UPDATE_GOLDENS").is_some() {
+            std::fs::create_dir_all(golden_dir()).unwrap();
+            actual.save(&golden_path).unwrap();
+            return;
+        }
+
+        let expected = match image::open(&golden_path) {
+            Ok(image) => image.to_rgba8(),
+            Err(err) => {
+                let actual_path = write_output(name, "actual", &actual);
+                panic!(
+                    "cannot open golden image {} ({}); the rendered frame is at {}. Run with UPDATE_GOLDENS=1 to create it",
+                    golden_path.display(),
+                    err,
+                    actual_path.display()
+                );
+            }
+        };
+
+        if let Err(message) = compare(&expected, &actual, name) {
+            panic!("{}: {}", name, message);
+        }
+    }
+}
+
+fn golden_dir() -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR")).join("tests").join("golden")
+}
+
+fn write_output(name: &str, kind: &str, image: &RgbaImage) -> PathBuf {
+    let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("target").join("snapshots");
+    std::fs::create_dir_all(&dir).unwrap();
+    let path = dir.join(format!("{}.{}.png", name, kind));
+    image.save(&path).unwrap();
+    path
+}
+
+// 見た目で分かる違いのあるピクセルが許容範囲を超えていたら、差分の画像を書き出してエラーにする
+fn compare(expected: &RgbaImage, actual: &RgbaImage, name: &str) -> Result<(), String> {
+    if expected.dimensions() != actual.dimensions() {
+        let actual_path = write_output(name, "actual", actual);
+        return Err(format!(
+            "size differs: expected {:?}, got {:?} (rendered frame at {})",
+            expected.dimensions(),
+            actual.dimensions(),
+            actual_path.display()
+        ));
+    }
+
+    // 差分の画像は、正解の画像を薄くした上に違うピクセルを赤で描く
+    let max_delta = 35215.0 * COLOR_THRESHOLD * COLOR_THRESHOLD;
+    let mut diff = RgbaImage::new(expected.width(), expected.height());
+    let mut different = 0;
+    for (x, y, expected_pixel) in expected.enumerate_pixels() {
+        let actual_pixel = actual.get_pixel(x, y);
+        if color_delta(expected_pixel, actual_pixel) > max_delta {
+            different += 1;
+            diff.put_pixel(x, y, Rgba([255, 0, 0, 255]));
+        } else {
+            let gray = (255.0 - (255.0 - luma(expected_pixel)) * 0.1) as u8;
+            diff.put_pixel(x, y, Rgba([gray, gray, gray, 255]));
+        }
+    }
+
+    let total = (expected.width() * expected.height()) as f64;
+    if different as f64 / total <= MAX_DIFF_RATIO {
+        return Ok(());
+    }
+    let actual_path = write_output(name, "actual", actual);
+    let diff_path = write_output(name, "diff", &diff);
+    Err(format!(
+        "{} of {} pixels differ (allowed {:.1}%); see {} and {}",
+        different,
+        total,
+        MAX_DIFF_RATIO * 100.0,
+        actual_path.display(),
+        diff_path.display()
+    ))
+}
+
+// 黒の上に重ねたときの色
+fn rgb(pixel: &Rgba<u8>) -> [f64; 3] {
+    let alpha = pixel[3] as f64 / 255.0;
+    [pixel[0] as f64 * alpha, pixel[1] as f64 * alpha, pixel[2] as f64 * alpha]
+}
+
+fn luma(pixel: &Rgba<u8>) -> f64 {
+    let [r, g, b] = rgb(pixel);
+    r * 0.29889531 + g * 0.58662247 + b * 0.11448223
+}
+
+// 人の目に感じる色の差（YIQ 色空間での重み付きの二乗距離、最大 35215）
+fn color_delta(a: &Rgba<u8>, b: &Rgba<u8>) -> f64 {
+    let [r1, g1, b1] = rgb(a);
+    let [r2, g2, b2] = rgb(b);
+    let y = (r1 - r2) * 0.29889531 + (g1 - g2) * 0.58662247 + (b1 - b2) * 0.11448223;
+    let i = (r1 - r2) * 0.59597799 - (g1 - g2) * 0.27417610 - (b1 - b2) * 0.32180189;
+    let q = (r1 - r2) * 0.21147017 - (g1 - g2) * 0.52261711 + (b1 - b2) * 0.31114694;
+    0.5053 * y * y + 0.299 * i * i + 0.1957 * q * q
+}